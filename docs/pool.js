@@ -0,0 +1,46 @@
+// Runs the same search in several workers, each with its own random
+// mutations. Whenever one finds a better layout, the others continue from it.
+export class LayoutPool {
+    constructor(onProgress, count = Math.min(navigator.hardwareConcurrency || 2, 8)) {
+        this.onProgress = onProgress
+        this.workers = Array.from({length: count}, () => new Worker("worker.js", {type: "module"}))
+        this.version = 0
+        this.workers.forEach(worker => worker.onmessage = e => this.received(worker, e.data))
+    }
+
+    run(version, source) {
+        this.version = version
+        this.bestScore = Infinity
+        this.done = new Set()
+        this.workers.forEach(worker => worker.postMessage({version, source}))
+    }
+
+    stop() {
+        this.workers.forEach(worker => worker.postMessage({version: this.version, stop: true}))
+        this.version = null
+    }
+
+    received(worker, {version, output, score, done, layout, errors}) {
+        if (version !== this.version) return
+        // Every worker finds the same errors in the source
+        if (errors) {
+            this.onProgress({version, errors})
+            return
+        }
+        if (done) this.done.add(worker)
+        else this.done.delete(worker)
+        if (score < this.bestScore) {
+            this.bestScore = score
+            this.bestOutput = output
+            this.workers
+                .filter(other => other !== worker)
+                .forEach(other => other.postMessage({version, offer: layout}))
+        }
+        this.onProgress({
+            version,
+            output: this.bestOutput,
+            score: this.bestScore,
+            done: this.done.size === this.workers.length
+        })
+    }
+}
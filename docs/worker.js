@@ -1,11 +1,45 @@
-import init, {md_to_md} from './obm.js';
+import init, {LayoutSession, md_errors} from './obm.js';
 
 
 let ready = init()
 
+// Only the latest source is worked on, older sessions are dropped
+let session = null
+let running = false
+
 onmessage = async (e) => {
     await ready
-    const {version, source} = e.data;
-    let output = md_to_md(source, 80, 80)
-    postMessage({version, output})
-}
\ No newline at end of file
+    const {version, source, stop, offer} = e.data;
+    if (offer !== undefined) {
+        // A better layout found by another worker, the search goes on from it
+        if (session && session.version === version && session.offer(offer) && !running)
+            run(session, undefined)
+        return
+    }
+    session?.free()
+    session = null
+    if (stop) return
+    try {
+        session = new LayoutSession(source, 80, 80)
+    } catch {
+        // Lines and columns of the broken instructions, instead of a chart
+        postMessage({version, errors: JSON.parse(md_errors(source, 80, 80))})
+        return
+    }
+    session.version = version
+    run(session, undefined)
+}
+
+// Search in slices of 100ms, posting each better layout, and give the event
+// loop a chance to deliver new messages in between
+function run(current, previousScore) {
+    running = current === session
+    if (!running) return
+    const {md, score, done} = current.step(100)
+    if (score !== previousScore || done) {
+        const layout = score !== previousScore ? current.best_layout() : undefined
+        postMessage({version: current.version, output: md, score, done, layout})
+    }
+    if (done) running = false
+    else setTimeout(() => run(current, score), 0)
+}
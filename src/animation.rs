@@ -0,0 +1,143 @@
+use crate::canvas::{display_width, wrap};
+use crate::{Individual, StoryStep};
+
+// Size of a character in the svg, for a 14px monospace font
+const CELL_WIDTH: f32 = 8.4;
+const CELL_HEIGHT: f32 = 17.0;
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl Individual {
+    // The lines shown for each step, the chart then its text wrapped to the
+    // width of the chart, or 60 columns for small charts. Steps with nothing
+    // to show are left out.
+    fn frames(&self, story: &[StoryStep]) -> Vec<Vec<String>> {
+        story
+            .iter()
+            .filter(|step| !step.visible_nodes_ids.is_empty() || !step.md.trim().is_empty())
+            .map(|step| {
+                let mut lines: Vec<String> =
+                    self.to_string(step).lines().map(String::from).collect();
+                let width = lines.iter().map(|l| display_width(l)).max().unwrap_or(0);
+                for paragraph in step.md.trim().lines() {
+                    lines.extend(wrap(paragraph, width.max(60)));
+                }
+                lines
+            })
+            .collect()
+    }
+
+    // An asciinema recording, showing each step for seconds_per_step
+    pub fn to_asciicast(&self, story: &[StoryStep], seconds_per_step: f32) -> String {
+        let frames = self.frames(story);
+        let width = frames
+            .iter()
+            .flatten()
+            .map(|l| display_width(l))
+            .max()
+            .unwrap_or(0);
+        let height = frames.iter().map(|f| f.len()).max().unwrap_or(0);
+
+        let header = serde_json::json!({"version": 2, "width": width, "height": height});
+        let mut out = format!("{}\n", header);
+        for (index, frame) in frames.iter().enumerate() {
+            // Clear the screen and write the frame from the top left corner
+            let data = format!("\x1b[2J\x1b[H{}", frame.join("\r\n"));
+            let event = serde_json::json!([index as f32 * seconds_per_step, "o", data]);
+            out.push_str(&format!("{}\n", event));
+        }
+        // Players stop at the last event, this one keeps the last step on screen
+        let end = serde_json::json!([frames.len() as f32 * seconds_per_step, "o", ""]);
+        out.push_str(&format!("{}\n", end));
+        out
+    }
+
+    // An svg showing each step for seconds_per_step, in a loop. Every step is
+    // a group hidden by default, and shown during its part of the loop.
+    pub fn to_animated_svg(&self, story: &[StoryStep], seconds_per_step: f32) -> String {
+        let frames = self.frames(story);
+        let columns = frames
+            .iter()
+            .flatten()
+            .map(|l| display_width(l))
+            .max()
+            .unwrap_or(0);
+        let rows = frames.iter().map(|f| f.len()).max().unwrap_or(0);
+        let width = (columns as f32 + 2.0) * CELL_WIDTH;
+        let height = (rows as f32 + 1.0) * CELL_HEIGHT;
+        let total = frames.len() as f32 * seconds_per_step;
+
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" viewBox=\"0 0 {:.0} {:.0}\">\n",
+            width, height, width, height
+        );
+        out.push_str(&format!(
+            "<style>\n\
+             text {{ font-family: monospace; font-size: 14px; white-space: pre; fill: #222; }}\n\
+             g {{ visibility: hidden; animation: step {}s step-end infinite; }}\n\
+             @keyframes step {{ 0% {{ visibility: visible; }} {}% {{ visibility: hidden; }} }}\n\
+             </style>\n",
+            total,
+            100.0 / frames.len().max(1) as f32
+        ));
+        out.push_str(&format!(
+            "<rect width=\"{:.0}\" height=\"{:.0}\" fill=\"#fff\"/>\n",
+            width, height
+        ));
+        for (index, frame) in frames.iter().enumerate() {
+            out.push_str(&format!(
+                "<g style=\"animation-delay: {}s\">\n",
+                index as f32 * seconds_per_step
+            ));
+            for (row, line) in frame.iter().enumerate() {
+                out.push_str(&format!(
+                    "<text x=\"{:.1}\" y=\"{:.1}\" xml:space=\"preserve\">{}</text>\n",
+                    CELL_WIDTH,
+                    (row as f32 + 1.0) * CELL_HEIGHT,
+                    escape_xml(line)
+                ));
+            }
+            out.push_str("</g>\n");
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+#[test]
+fn to_asciicast_test() {
+    let (individual, story) =
+        Individual::from_string("# Title\nA -> B\nFirst.\nB -> C\nSecond.", 80, 40);
+    let cast = individual.to_asciicast(&story, 2.0);
+    let lines: Vec<serde_json::Value> = cast
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+
+    assert_eq!(lines[0]["version"], 2);
+    assert!(lines[0]["width"].as_u64().unwrap() > 0);
+    assert_eq!(lines.len(), 1 + 3 + 1);
+    assert_eq!(
+        lines[1],
+        serde_json::json!([0.0, "o", "\x1b[2J\x1b[H# Title"])
+    );
+    assert_eq!(lines[2][0], 2.0);
+    assert!(lines[3][2].as_str().unwrap().ends_with("Second."));
+    assert_eq!(lines[4], serde_json::json!([6.0, "o", ""]));
+}
+
+#[test]
+fn to_animated_svg_test() {
+    let (individual, story) = Individual::from_string("A -> B\nFirst <step>.\nB -> C", 80, 40);
+    let svg = individual.to_animated_svg(&story, 3.0);
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.contains("animation: step 6s step-end infinite"));
+    assert!(svg.contains("50% { visibility: hidden; }"));
+    assert_eq!(svg.matches("<g style").count(), 2);
+    assert!(svg.contains("<g style=\"animation-delay: 3s\">"));
+    assert!(svg.contains(">First &lt;step&gt;.</text>"));
+}
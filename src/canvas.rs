@@ -0,0 +1,153 @@
+use std::cmp::{max, min};
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// Number of terminal columns a single grapheme takes. Combining accents are
+// part of their grapheme, and emoji sequences are clamped to a double cell.
+fn grapheme_width(grapheme: &str) -> usize {
+    grapheme.width().clamp(1, 2)
+}
+
+pub fn display_width(str: &str) -> i32 {
+    str.graphemes(true).map(grapheme_width).sum::<usize>() as i32
+}
+
+// Split a text on spaces so that no line is wider than max_width, only cutting
+// words that wouldn't fit on a line of their own.
+pub fn wrap(text: &str, max_width: i32) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && display_width(&line) + 1 + display_width(word) > max_width {
+            lines.push(line);
+            line = String::new();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        for grapheme in word.graphemes(true) {
+            if !line.is_empty() && display_width(&line) + display_width(grapheme) > max_width {
+                lines.push(line);
+                line = String::new();
+            }
+            line.push_str(grapheme);
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+// A grid of terminal cells. A wide grapheme is stored in its first cell, and
+// the cell right after it is left empty, so that joining a line gives back a
+// string whose display width is the number of cells.
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    cells: Vec<Vec<String>>,
+    xmin: usize,
+    xmax: usize,
+    ymin: usize,
+    ymax: usize,
+}
+
+impl Canvas {
+    pub fn new(width: i32, height: i32) -> Canvas {
+        let width = width as usize;
+        let height = height as usize;
+        Canvas {
+            width,
+            height,
+            cells: vec![vec![" ".to_string(); width]; height],
+            xmin: width,
+            xmax: 0,
+            ymin: height,
+            ymax: 0,
+        }
+    }
+
+    fn in_bounds(&self, x: usize, y: usize) -> bool {
+        x > 0 && x < self.width && y > 0 && y < self.height
+    }
+
+    // Make sure no half of a wide grapheme is left behind when writing over a cell
+    fn clear_cell(&mut self, x: usize, y: usize) {
+        if self.cells[y][x].is_empty() && x > 0 {
+            self.cells[y][x - 1] = " ".to_string();
+        }
+        if grapheme_width(&self.cells[y][x]) == 2 && x + 1 < self.width {
+            self.cells[y][x + 1] = " ".to_string();
+        }
+        self.cells[y][x] = " ".to_string();
+    }
+
+    pub fn draw(&mut self, x: i32, y: i32, str: &str) {
+        if x < 0 || y < 0 {
+            return;
+        }
+        let (mut x, y) = (x as usize, y as usize);
+        for grapheme in str.graphemes(true) {
+            let width = grapheme_width(grapheme);
+            if (0..width).all(|dx| self.in_bounds(x + dx, y)) {
+                for dx in 0..width {
+                    self.clear_cell(x + dx, y);
+                }
+                self.cells[y][x] = grapheme.to_string();
+                for dx in 1..width {
+                    self.cells[y][x + dx] = String::new();
+                }
+                self.xmin = min(self.xmin, x);
+                self.xmax = max(self.xmax, x + width - 1);
+                self.ymin = min(self.ymin, y);
+                self.ymax = max(self.ymax, y);
+            }
+            x += width;
+        }
+    }
+
+    // Crop the canvas to what was drawn, and indent it as a markdown code block
+    pub fn to_markdown(&self) -> String {
+        if self.ymin > self.ymax {
+            return String::new();
+        }
+        self.cells[self.ymin..=self.ymax]
+            .iter()
+            .map(|line| format!("    {}", line[self.xmin..=self.xmax].concat()))
+            .collect::<Vec<String>>()
+            .join("\n")
+            + "\n"
+    }
+}
+
+#[test]
+fn display_width_test() {
+    assert_eq!(display_width("Barista"), 7);
+    assert_eq!(display_width("東京"), 4);
+    assert_eq!(display_width("☕"), 2);
+    assert_eq!(display_width("Cafe\u{301}"), 4);
+    assert_eq!(display_width("👩‍🍳"), 2);
+}
+
+#[test]
+fn wrap_test() {
+    assert_eq!(wrap("Barista", 10), vec!["Barista"]);
+    assert_eq!(wrap("Espresso Machine", 10), vec!["Espresso", "Machine"]);
+    assert_eq!(wrap("a b c d", 3), vec!["a b", "c d"]);
+    assert_eq!(wrap("Refrigerator", 5), vec!["Refri", "gerat", "or"]);
+    assert_eq!(wrap("東京 大阪", 4), vec!["東京", "大阪"]);
+    assert_eq!(wrap("", 4), vec![""]);
+}
+
+#[test]
+fn canvas_wide_graphemes_test() {
+    let mut canvas = Canvas::new(20, 5);
+    canvas.draw(1, 1, "東京");
+    canvas.draw(1, 2, "abcd");
+    assert_eq!(canvas.to_markdown(), "    東京\n    abcd\n");
+
+    // Writing over the second half of a wide grapheme erases it
+    canvas.draw(2, 1, "x");
+    assert_eq!(canvas.to_markdown(), "     x京\n    abcd\n");
+}
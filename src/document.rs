@@ -0,0 +1,357 @@
+use std::collections::HashSet;
+
+use crate::{has_arrow, StoryStep};
+
+// Which lines of a document are chart instructions
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum Instructions {
+    // Any line with an arrow, unless it is a comment or has html in it, and
+    // any line starting with remove:, !focus, !highlight or !alias, even in
+    // the middle of prose
+    #[default]
+    Arrows,
+    // Only lines indented with 4 spaces or a tab, and fenced ```obm blocks, so
+    // that prose like "convert A -> B" stays prose
+    Blocks,
+}
+
+// Where charts are drawn in the output
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum Output {
+    // Before the text of every step, highlighting what the step mentions
+    #[default]
+    Story,
+    // Once, where the first instruction is, with everything visible
+    Single,
+    // Only at <!-- obm:render --> comments, highlighting what was mentioned
+    // since the previous one
+    Markers,
+}
+
+// Settings written in the document itself, in html comments that markdown
+// renderers hide, like <!-- obm:instructions=blocks -->
+#[derive(PartialEq, Debug, Default)]
+pub struct DocumentOptions {
+    pub instructions: Instructions,
+    pub output: Output,
+    // <!-- obm:highlight=changes --> marks what each step shows for the first time
+    pub highlight_changes: bool,
+    // <!-- obm:fit=grid --> rounds boxes and envelopes up to whole grid cells
+    pub fit_to_grid: bool,
+}
+
+impl DocumentOptions {
+    pub fn parse(document: &str) -> DocumentOptions {
+        let mut options = DocumentOptions::default();
+        for (key, value) in document.lines().flat_map(directive) {
+            match (key, value) {
+                ("instructions", "blocks") => options.instructions = Instructions::Blocks,
+                ("instructions", "arrows") => options.instructions = Instructions::Arrows,
+                ("highlight", "changes") => options.highlight_changes = true,
+                ("highlight", "mentions") => options.highlight_changes = false,
+                ("fit", "grid") => options.fit_to_grid = true,
+                ("fit", "content") => options.fit_to_grid = false,
+                ("output", "story") => options.output = Output::Story,
+                ("output", "single") => options.output = Output::Single,
+                ("output", "markers") => options.output = Output::Markers,
+                _ => {}
+            }
+        }
+        options
+    }
+}
+
+// The key=value pairs of an obm comment, a word alone gives an empty value
+pub fn directive(line: &str) -> Vec<(&str, &str)> {
+    let Some(content) = line
+        .trim()
+        .strip_prefix("<!--")
+        .and_then(|l| l.strip_suffix("-->"))
+        .and_then(|l| l.trim().strip_prefix("obm:"))
+    else {
+        return vec![];
+    };
+    content
+        .split_whitespace()
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+        .collect()
+}
+
+impl Output {
+    // Regroup the steps of a story so that charts are only drawn where this
+    // output mode wants them
+    pub fn arrange(self, story: Vec<StoryStep>) -> Vec<StoryStep> {
+        match self {
+            Output::Story => story,
+            Output::Single => {
+                let first = story
+                    .iter()
+                    .position(|s| !s.visible_nodes_ids.is_empty())
+                    .unwrap_or(story.len());
+                let mut before = StoryStep::default();
+                let mut chart = StoryStep::default();
+                for (index, step) in story.into_iter().enumerate() {
+                    if index < first {
+                        before.md.push_str(&step.md);
+                        continue;
+                    }
+                    chart
+                        .visible_nodes_ids
+                        .extend(step.visible_nodes_ids.iter());
+                    chart.visible_link_ids.extend(step.visible_link_ids.iter());
+                    chart.md.push_str(&step.md);
+                }
+                chart.highlighted_nodes_ids = chart.visible_nodes_ids.clone();
+                chart.highlighted_link_ids = chart.visible_link_ids.clone();
+                vec![before, chart]
+            }
+            Output::Markers => {
+                let mut steps = vec![StoryStep::default()];
+                let mut highlighted_nodes_ids = HashSet::new();
+                let mut highlighted_link_ids = HashSet::new();
+                for step in story {
+                    highlighted_nodes_ids.extend(step.highlighted_nodes_ids.iter());
+                    highlighted_link_ids.extend(step.highlighted_link_ids.iter());
+                    for line in step.md.lines() {
+                        if directive(line).contains(&("render", "")) {
+                            steps.push(StoryStep {
+                                visible_nodes_ids: step.visible_nodes_ids.clone(),
+                                visible_link_ids: step.visible_link_ids.clone(),
+                                highlighted_nodes_ids: std::mem::take(&mut highlighted_nodes_ids),
+                                highlighted_link_ids: std::mem::take(&mut highlighted_link_ids),
+                                md: String::new(),
+                                dim_others: false,
+                                changes: None,
+                            });
+                        } else {
+                            let md = &mut steps.last_mut().unwrap().md;
+                            md.push_str(line);
+                            md.push('\n');
+                        }
+                    }
+                }
+                steps
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Line<'a> {
+    // The text of an instruction, without its indentation
+    Instruction(&'a str),
+    // A node to highlight with everything inside it, dimming the rest
+    Focus(&'a str),
+    // Nodes and links to highlight again, like "!highlight A -> B"
+    Highlight(&'a str),
+    // A node to hide from this step on, like "remove: Cafe:Cashier"
+    Remove(&'a str),
+    // A short name for a path, and maybe the name to draw instead of its own,
+    // like "!alias wand = Cafe:Espresso Machine:Steam Wand "Wand""
+    Alias(&'a str, &'a str, Option<&'a str>),
+    // Passed through verbatim
+    Text(&'a str),
+    // The fences of obm blocks, and what isn't an instruction inside them
+    Skipped,
+}
+
+// "wand = Cafe:Steam Wand "Wand"" as the alias, the path, and the name drawn
+// in quotes at the end. Without an equal sign, the whole line is the name.
+fn alias(declaration: &str) -> (&str, &str, Option<&str>) {
+    let Some((name, path)) = declaration.split_once('=') else {
+        return (declaration.trim(), "", None);
+    };
+    let (name, path) = (name.trim(), path.trim());
+    let quoted = path
+        .strip_suffix('"')
+        .and_then(|p| p.rsplit_once(" \""))
+        .filter(|(path, display)| !path.trim().is_empty() && !display.trim().is_empty());
+    match quoted {
+        Some((path, display)) => (name, path.trim(), Some(display.trim())),
+        None => (name, path, None),
+    }
+}
+
+fn is_fence(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+pub fn document_lines<'a>(document: &'a str, options: &DocumentOptions) -> Vec<Line<'a>> {
+    // Some(true) inside a ```obm block, Some(false) inside any other code block
+    let mut fence: Option<bool> = None;
+    let mut lines = vec![];
+    for line in document.lines() {
+        let trimmed = line.trim();
+        if is_fence(line) {
+            let obm = match fence {
+                None => {
+                    let obm = trimmed.trim_start_matches(['`', '~']).trim() == "obm";
+                    fence = Some(obm);
+                    obm
+                }
+                Some(obm) => {
+                    fence = None;
+                    obm
+                }
+            };
+            lines.push(if obm { Line::Skipped } else { Line::Text(line) });
+            continue;
+        }
+
+        let syntax = !trimmed.starts_with("//") && !line.contains('<');
+        let instruction = if syntax && trimmed.starts_with("!focus ") {
+            Some(Line::Focus(trimmed["!focus ".len()..].trim()))
+        } else if syntax && trimmed.starts_with("!highlight ") {
+            Some(Line::Highlight(trimmed["!highlight ".len()..].trim()))
+        } else if syntax && trimmed.starts_with("remove:") {
+            Some(Line::Remove(trimmed["remove:".len()..].trim()))
+        } else if syntax && trimmed.starts_with("!alias ") {
+            let (name, path, display) = alias(&trimmed["!alias ".len()..]);
+            Some(Line::Alias(name, path, display))
+        } else if syntax && has_arrow(line) {
+            Some(Line::Instruction(trimmed))
+        } else {
+            None
+        };
+        let indented = line.starts_with("    ") || line.starts_with('\t');
+        lines.push(match (instruction, fence, options.instructions) {
+            (Some(instruction), Some(true), _) => instruction,
+            (None, Some(true), _) => Line::Skipped,
+            // Other code blocks can show the syntax without declaring anything
+            (Some(Line::Alias(..)), Some(false), _) => Line::Text(line),
+            (Some(instruction), _, Instructions::Arrows) => instruction,
+            (Some(instruction), None, Instructions::Blocks) if indented => instruction,
+            _ => Line::Text(line),
+        });
+    }
+    lines
+}
+
+#[test]
+fn directive_test() {
+    assert_eq!(
+        directive("<!-- obm:instructions=blocks -->"),
+        vec![("instructions", "blocks")]
+    );
+    assert_eq!(directive(" <!--obm:render-->"), vec![("render", "")]);
+    assert_eq!(directive("<!-- a comment -->"), vec![]);
+    assert_eq!(
+        DocumentOptions::parse("# Title\n<!-- obm:instructions=blocks -->\n").instructions,
+        Instructions::Blocks
+    );
+    assert!(DocumentOptions::parse("<!-- obm:fit=grid -->").fit_to_grid);
+}
+
+#[test]
+fn document_lines_test() {
+    let document = [
+        "<!-- obm:instructions=blocks -->",
+        "Convert A -> B first.",
+        "    A -> B",
+        "```rust",
+        "    let f = |a| a -> b;",
+        "```",
+        "```obm",
+        "// the cafe",
+        "!alias barista = Cafe:Barista",
+        "barista -> Customer",
+        "remove: barista",
+        "!focus Cafe",
+        "!highlight Customer -> Cafe",
+        "!important",
+        "```",
+    ]
+    .join("\n");
+    let options = DocumentOptions::parse(&document);
+    assert_eq!(
+        document_lines(&document, &options),
+        vec![
+            Line::Text("<!-- obm:instructions=blocks -->"),
+            Line::Text("Convert A -> B first."),
+            Line::Instruction("A -> B"),
+            Line::Text("```rust"),
+            Line::Text("    let f = |a| a -> b;"),
+            Line::Text("```"),
+            Line::Skipped,
+            Line::Skipped,
+            Line::Alias("barista", "Cafe:Barista", None),
+            Line::Instruction("barista -> Customer"),
+            Line::Remove("barista"),
+            Line::Focus("Cafe"),
+            Line::Highlight("Customer -> Cafe"),
+            Line::Skipped,
+            Line::Skipped,
+        ]
+    );
+
+    // Without the directive, any line with an arrow is an instruction
+    assert_eq!(
+        document_lines("Convert A -> B first.", &DocumentOptions::default()),
+        vec![Line::Instruction("Convert A -> B first.")]
+    );
+    assert_eq!(
+        document_lines(
+            "!alias db = DB {shape=cylinder} \"Orders DB\"\nSo x = 2 and y == 3\n!alias q=Q",
+            &DocumentOptions::default()
+        ),
+        vec![
+            Line::Alias("db", "DB {shape=cylinder}", Some("Orders DB")),
+            Line::Text("So x = 2 and y == 3"),
+            Line::Alias("q", "Q", None),
+        ]
+    );
+
+    // Settings in other code blocks stay as they are
+    let document = "```toml\nname = \"obm\"\n!alias db = DB\n```";
+    assert_eq!(
+        document_lines(document, &DocumentOptions::default()),
+        vec![
+            Line::Text("```toml"),
+            Line::Text("name = \"obm\""),
+            Line::Text("!alias db = DB"),
+            Line::Text("```"),
+        ]
+    );
+}
+
+#[test]
+fn output_test() {
+    let document = |output: &str| {
+        [
+            &format!("<!-- obm:output={} -->", output),
+            "# Coffee",
+            "Customer -> Cafe:Cashier",
+            "The customer orders.",
+            "<!-- obm:render -->",
+            "Cafe:Cashier -> Cafe:Barista",
+            "The cashier relays the order.",
+            "Cafe:Barista -> Customer",
+            "<!-- obm:render -->",
+            "The barista serves the coffee.",
+        ]
+        .join("\n")
+    };
+
+    let (_, story) = crate::Individual::from_string(&document("single"), 80, 40);
+    assert_eq!(story.len(), 2);
+    assert!(story[0].visible_nodes_ids.is_empty());
+    assert_eq!(story[0].md, "<!-- obm:output=single -->\n# Coffee\n");
+    assert_eq!(story[1].visible_nodes_ids.len(), 4);
+    assert_eq!(story[1].highlighted_link_ids.len(), 3);
+    assert!(story[1]
+        .md
+        .starts_with("The customer orders.\n<!-- obm:render -->\n"));
+
+    let (_, story) = crate::Individual::from_string(&document("markers"), 80, 40);
+    assert_eq!(story.len(), 3);
+    assert_eq!(
+        story[0].md,
+        "<!-- obm:output=markers -->\n# Coffee\nThe customer orders.\n"
+    );
+    assert_eq!(story[1].visible_nodes_ids.len(), 3);
+    assert_eq!(story[1].md, "The cashier relays the order.\n");
+    assert_eq!(story[2].visible_nodes_ids.len(), 4);
+    assert_eq!(story[2].highlighted_link_ids.len(), 2);
+    assert_eq!(story[2].md, "The barista serves the coffee.\n");
+}
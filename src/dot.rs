@@ -0,0 +1,969 @@
+use crate::{
+    size_error, BorderStyle, Individual, LinkStyle, Node, NodeSpec, ParseError, Shape, StoryStep,
+};
+
+#[derive(PartialEq, Debug, Clone)]
+enum Token {
+    Id(String),
+    // Quoted strings can be concatenated with +, bare ids can't
+    Quoted(String),
+    OpenBrace,
+    CloseBrace,
+    OpenBracket,
+    CloseBracket,
+    Equal,
+    Semicolon,
+    Comma,
+    Colon,
+    Plus,
+    Arrow,
+}
+
+struct Located {
+    token: Token,
+    line: usize,
+    column: usize,
+}
+
+fn error(line: usize, column: usize, message: &str) -> ParseError {
+    ParseError {
+        line,
+        column,
+        message: message.to_string(),
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Located>, ParseError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = vec![];
+    let (mut index, mut line, mut column) = (0, 1, 1);
+
+    // Move the cursor forward, keeping track of lines and columns
+    let advance = |index: &mut usize, line: &mut usize, column: &mut usize| {
+        if chars[*index] == '\n' {
+            *line += 1;
+            *column = 1;
+        } else {
+            *column += 1;
+        }
+        *index += 1;
+    };
+
+    while index < chars.len() {
+        let (start_line, start_column) = (line, column);
+        let char = chars[index];
+        let next = chars.get(index + 1).copied();
+        let token = match char {
+            _ if char.is_whitespace() => None,
+            // Preprocessor lines are ignored like comments
+            '#' if column == 1 => {
+                while index < chars.len() && chars[index] != '\n' {
+                    advance(&mut index, &mut line, &mut column);
+                }
+                continue;
+            }
+            '/' if next == Some('/') => {
+                while index < chars.len() && chars[index] != '\n' {
+                    advance(&mut index, &mut line, &mut column);
+                }
+                continue;
+            }
+            '/' if next == Some('*') => {
+                while index < chars.len()
+                    && !(chars[index] == '*' && chars.get(index + 1) == Some(&'/'))
+                {
+                    advance(&mut index, &mut line, &mut column);
+                }
+                if index >= chars.len() {
+                    return Err(error(start_line, start_column, "unclosed comment"));
+                }
+                advance(&mut index, &mut line, &mut column);
+                advance(&mut index, &mut line, &mut column);
+                continue;
+            }
+            '-' if next == Some('>') || next == Some('-') => {
+                advance(&mut index, &mut line, &mut column);
+                Some(Token::Arrow)
+            }
+            '{' => Some(Token::OpenBrace),
+            '}' => Some(Token::CloseBrace),
+            '[' => Some(Token::OpenBracket),
+            ']' => Some(Token::CloseBracket),
+            '=' => Some(Token::Equal),
+            ';' => Some(Token::Semicolon),
+            ',' => Some(Token::Comma),
+            ':' => Some(Token::Colon),
+            '+' => Some(Token::Plus),
+            '"' => {
+                let mut string = String::new();
+                advance(&mut index, &mut line, &mut column);
+                while index < chars.len() && chars[index] != '"' {
+                    if chars[index] == '\\' && index + 1 < chars.len() {
+                        advance(&mut index, &mut line, &mut column);
+                        match chars[index] {
+                            // Line breaks in labels, obm wraps names by itself
+                            'n' | 'l' | 'r' => string.push(' '),
+                            '\n' => {}
+                            escaped => string.push(escaped),
+                        }
+                    } else {
+                        string.push(chars[index]);
+                    }
+                    advance(&mut index, &mut line, &mut column);
+                }
+                if index >= chars.len() {
+                    return Err(error(start_line, start_column, "unclosed string"));
+                }
+                Some(Token::Quoted(string))
+            }
+            '<' => {
+                // HTML labels are kept as they are
+                let mut string = String::new();
+                let mut depth = 0;
+                loop {
+                    if index >= chars.len() {
+                        return Err(error(start_line, start_column, "unclosed html label"));
+                    }
+                    match chars[index] {
+                        '<' => depth += 1,
+                        '>' => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                    if depth > 1 || chars[index] != '<' {
+                        string.push(chars[index]);
+                    }
+                    advance(&mut index, &mut line, &mut column);
+                }
+                Some(Token::Id(string))
+            }
+            // Numerals can be negative, like -1 or -.5
+            _ if char.is_alphanumeric()
+                || char == '_'
+                || char == '.'
+                || (char == '-'
+                    && next.is_some_and(|next| next.is_ascii_digit() || next == '.')) =>
+            {
+                let mut string = String::new();
+                if char == '-' {
+                    string.push(char);
+                    advance(&mut index, &mut line, &mut column);
+                }
+                while index < chars.len()
+                    && (chars[index].is_alphanumeric()
+                        || chars[index] == '_'
+                        || chars[index] == '.')
+                {
+                    string.push(chars[index]);
+                    advance(&mut index, &mut line, &mut column);
+                }
+                tokens.push(Located {
+                    token: Token::Id(string),
+                    line: start_line,
+                    column: start_column,
+                });
+                continue;
+            }
+            _ => {
+                return Err(error(
+                    start_line,
+                    start_column,
+                    &format!("unexpected character '{}'", char),
+                ))
+            }
+        };
+        if let Some(token) = token {
+            tokens.push(Located {
+                token,
+                line: start_line,
+                column: start_column,
+            });
+        }
+        advance(&mut index, &mut line, &mut column);
+    }
+    Ok(tokens)
+}
+
+// A subgraph named cluster_*, drawn as a box around its nodes
+struct Cluster {
+    name: String,
+    label: String,
+    description: Option<String>,
+    parent: Option<usize>,
+}
+
+struct DotNode {
+    id: String,
+    attributes: Vec<(String, String)>,
+    cluster: Option<usize>,
+}
+
+struct DotEdge {
+    from: String,
+    to: String,
+    attributes: Vec<(String, String)>,
+}
+
+// Default attributes set with "node [...]" and "edge [...]", they only apply
+// to the nodes and edges of the current subgraph
+#[derive(Clone, Default)]
+struct Scope {
+    cluster: Option<usize>,
+    node_attributes: Vec<(String, String)>,
+    edge_attributes: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+struct Parser {
+    tokens: Vec<Located>,
+    index: usize,
+    clusters: Vec<Cluster>,
+    nodes: Vec<DotNode>,
+    edges: Vec<DotEdge>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|t| &t.token)
+    }
+
+    fn error_here(&self, message: &str) -> ParseError {
+        match self.tokens.get(self.index).or(self.tokens.last()) {
+            Some(located) => error(located.line, located.column, message),
+            None => error(1, 1, message),
+        }
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token, what: &str) -> Result<(), ParseError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error_here(&format!("expected {}", what)))
+        }
+    }
+
+    fn keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Id(id)) if id.eq_ignore_ascii_case(keyword))
+    }
+
+    fn id(&mut self) -> Result<String, ParseError> {
+        match self.peek().cloned() {
+            Some(Token::Id(id)) => {
+                self.index += 1;
+                Ok(id)
+            }
+            Some(Token::Quoted(mut string)) => {
+                self.index += 1;
+                while self.peek() == Some(&Token::Plus) {
+                    self.index += 1;
+                    match self.peek().cloned() {
+                        Some(Token::Quoted(more)) => {
+                            self.index += 1;
+                            string.push_str(&more);
+                        }
+                        _ => return Err(self.error_here("expected a string after +")),
+                    }
+                }
+                Ok(string)
+            }
+            _ => Err(self.error_here("expected an id")),
+        }
+    }
+
+    fn graph(&mut self) -> Result<(), ParseError> {
+        if self.keyword("strict") {
+            self.index += 1;
+        }
+        if !(self.keyword("digraph") || self.keyword("graph")) {
+            return Err(self.error_here("expected digraph or graph"));
+        }
+        self.index += 1;
+        if self.peek() != Some(&Token::OpenBrace) {
+            self.id()?;
+        }
+        self.expect(&Token::OpenBrace, "{")?;
+        self.statements(&mut Scope::default())?;
+        self.expect(&Token::CloseBrace, "}")?;
+        if self.index < self.tokens.len() {
+            return Err(self.error_here("unexpected content after the graph"));
+        }
+        Ok(())
+    }
+
+    fn statements(&mut self, scope: &mut Scope) -> Result<Vec<String>, ParseError> {
+        let mut node_ids = vec![];
+        while !matches!(self.peek(), Some(Token::CloseBrace) | None) {
+            node_ids.extend(self.statement(scope)?);
+            while self.eat(&Token::Semicolon) {}
+        }
+        Ok(node_ids)
+    }
+
+    // Returns the ids of the nodes mentioned, for subgraphs used in edges
+    fn statement(&mut self, scope: &mut Scope) -> Result<Vec<String>, ParseError> {
+        for (keyword, is_node) in [("node", true), ("edge", false)] {
+            if self.keyword(keyword)
+                && self.tokens.get(self.index + 1).map(|t| &t.token) == Some(&Token::OpenBracket)
+            {
+                self.index += 1;
+                let attributes = self.attributes()?;
+                if is_node {
+                    scope.node_attributes.extend(attributes);
+                } else {
+                    scope.edge_attributes.extend(attributes);
+                }
+                return Ok(vec![]);
+            }
+        }
+        if self.keyword("graph")
+            && self.tokens.get(self.index + 1).map(|t| &t.token) == Some(&Token::OpenBracket)
+        {
+            self.index += 1;
+            let attributes = self.attributes()?;
+            self.set_cluster_label(scope, &attributes);
+            return Ok(vec![]);
+        }
+
+        let mut operands = vec![self.operand(scope)?];
+        if self.peek() == Some(&Token::Equal) {
+            // A graph attribute like label="Cafe"
+            self.index += 1;
+            let value = self.id()?;
+            let key = operands.pop().unwrap().pop().unwrap_or_default();
+            self.set_cluster_label(scope, &[(key, value)]);
+            return Ok(vec![]);
+        }
+        while self.eat(&Token::Arrow) {
+            operands.push(self.operand(scope)?);
+        }
+        let attributes = if self.peek() == Some(&Token::OpenBracket) {
+            self.attributes()?
+        } else {
+            vec![]
+        };
+
+        if operands.len() == 1 {
+            for id in operands[0].iter() {
+                self.mention_node(id, scope, &attributes);
+            }
+        } else {
+            let mut edge_attributes = scope.edge_attributes.clone();
+            edge_attributes.extend(attributes);
+            for pair in operands.windows(2) {
+                for from in pair[0].iter() {
+                    for to in pair[1].iter() {
+                        self.edges.push(DotEdge {
+                            from: from.clone(),
+                            to: to.clone(),
+                            attributes: edge_attributes.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(operands.concat())
+    }
+
+    fn set_cluster_label(&mut self, scope: &Scope, attributes: &[(String, String)]) {
+        if let Some(cluster) = scope.cluster {
+            for (key, value) in attributes {
+                if value.trim().is_empty() {
+                    continue;
+                }
+                match key.as_str() {
+                    "label" => self.clusters[cluster].label = value.clone(),
+                    "xlabel" => self.clusters[cluster].description = Some(value.clone()),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    // A node id, or a subgraph standing for all of its nodes
+    fn operand(&mut self, scope: &mut Scope) -> Result<Vec<String>, ParseError> {
+        if self.keyword("subgraph") || self.peek() == Some(&Token::OpenBrace) {
+            let mut inner = scope.clone();
+            if self.keyword("subgraph") {
+                self.index += 1;
+                if self.peek() != Some(&Token::OpenBrace) {
+                    let name = self.id()?;
+                    if let Some(label) = name.strip_prefix("cluster") {
+                        let label = label.trim_start_matches('_');
+                        self.clusters.push(Cluster {
+                            name: name.clone(),
+                            label: if label.is_empty() {
+                                name.clone()
+                            } else {
+                                label.to_string()
+                            },
+                            description: None,
+                            parent: scope.cluster,
+                        });
+                        inner.cluster = Some(self.clusters.len() - 1);
+                    }
+                }
+            }
+            self.expect(&Token::OpenBrace, "{")?;
+            let node_ids = self.statements(&mut inner)?;
+            self.expect(&Token::CloseBrace, "}")?;
+            return Ok(node_ids);
+        }
+
+        let start = self.index;
+        let id = self.id()?;
+        if id.trim().is_empty() {
+            let located = &self.tokens[start];
+            return Err(error(located.line, located.column, "empty node id"));
+        }
+        // Ports like a:n or a:port:sw only change where edges attach
+        while self.eat(&Token::Colon) {
+            self.id()?;
+        }
+        if self.peek() != Some(&Token::Equal) {
+            self.mention_node(&id, scope, &[]);
+        }
+        Ok(vec![id])
+    }
+
+    fn attributes(&mut self) -> Result<Vec<(String, String)>, ParseError> {
+        let mut attributes = vec![];
+        while self.eat(&Token::OpenBracket) {
+            while !self.eat(&Token::CloseBracket) {
+                let key = self.id()?;
+                let value = if self.eat(&Token::Equal) {
+                    self.id()?
+                } else {
+                    "true".to_string()
+                };
+                attributes.push((key, value));
+                while self.eat(&Token::Comma) || self.eat(&Token::Semicolon) {}
+            }
+        }
+        Ok(attributes)
+    }
+
+    // A node belongs to the innermost cluster it is mentioned in
+    fn mention_node(&mut self, id: &str, scope: &Scope, attributes: &[(String, String)]) {
+        let index = match self.nodes.iter().position(|n| n.id == id) {
+            Some(index) => index,
+            None => {
+                self.nodes.push(DotNode {
+                    id: id.to_string(),
+                    attributes: scope.node_attributes.clone(),
+                    cluster: None,
+                });
+                self.nodes.len() - 1
+            }
+        };
+        if let Some(cluster) = scope.cluster {
+            if self.cluster_depth(Some(cluster)) > self.cluster_depth(self.nodes[index].cluster) {
+                self.nodes[index].cluster = Some(cluster);
+            }
+        }
+        self.nodes[index]
+            .attributes
+            .extend(attributes.iter().cloned());
+    }
+
+    fn cluster_depth(&self, cluster: Option<usize>) -> usize {
+        let mut depth = 0;
+        let mut cursor = cluster;
+        while let Some(id) = cursor {
+            depth += 1;
+            cursor = self.clusters[id].parent;
+        }
+        depth
+    }
+}
+
+fn last_value<'a>(attributes: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .rev()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+// Graphviz shapes and styles translated to the closest obm attributes
+fn node_attributes(attributes: &[(String, String)]) -> Vec<(&'static str, &'static str)> {
+    let mut result = vec![];
+    match last_value(attributes, "shape") {
+        Some("cylinder") => result.push(("shape", "cylinder")),
+        Some("ellipse" | "oval" | "circle" | "doublecircle" | "Mrecord") => {
+            result.push(("shape", "round"))
+        }
+        Some("box" | "rect" | "rectangle" | "square" | "record") => result.push(("shape", "box")),
+        _ => {}
+    }
+    if let Some(style) = last_value(attributes, "style") {
+        for style in style.split(',').map(|s| s.trim()) {
+            match style {
+                "dashed" => result.push(("style", "dashed")),
+                "dotted" => result.push(("style", "dotted")),
+                "rounded" => result.push(("shape", "round")),
+                _ => {}
+            }
+        }
+    }
+    result
+}
+
+fn link_style(attributes: &[(String, String)]) -> LinkStyle {
+    match last_value(attributes, "style") {
+        Some("dashed") => LinkStyle::Dashed,
+        Some("dotted") => LinkStyle::Dotted,
+        Some("bold") => LinkStyle::Thick,
+        _ => LinkStyle::Solid,
+    }
+}
+
+impl Individual {
+    // Read a Graphviz graph. Clusters become parents of their nodes, and the
+    // whole graph is shown in a single story step. Each DOT id is a node of
+    // its own, even when its label is the same as another one.
+    pub fn from_dot(
+        source: &str,
+        width: i32,
+        height: i32,
+    ) -> Result<(Individual, Vec<StoryStep>), ParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            ..Default::default()
+        };
+        parser.graph()?;
+        if let Some(error) = size_error(width, height) {
+            return Err(error);
+        }
+
+        let mut individual = Individual::empty(width, height);
+        let mut step = StoryStep::default();
+
+        // Clusters are declared before the clusters they contain
+        let mut cluster_ids: Vec<usize> = vec![];
+        for cluster in parser.clusters.iter() {
+            let spec = NodeSpec {
+                name: &cluster.label,
+                description: cluster.description.as_deref().map(|d| d.trim()),
+                ..Default::default()
+            };
+            cluster_ids.push(individual.add_node(&spec, cluster.parent.map(|p| cluster_ids[p])));
+        }
+
+        // An invisible node stands for the cluster it is in, like the anchors
+        // to_dot writes, and edges to one outside of any cluster are dropped
+        let mut node_ids: Vec<Option<usize>> = vec![];
+        for node in parser.nodes.iter() {
+            let cluster = node.cluster.map(|c| cluster_ids[c]);
+            let invisible = last_value(&node.attributes, "style")
+                .is_some_and(|style| style.split(',').any(|s| s.trim() == "invis"));
+            if invisible {
+                node_ids.push(cluster);
+                continue;
+            }
+            let non_empty = |key: &str| {
+                last_value(&node.attributes, key)
+                    .filter(|value| !value.trim().is_empty())
+                    .map(|value| value.trim())
+            };
+            let spec = NodeSpec {
+                name: non_empty("label").unwrap_or(node.id.trim()),
+                description: non_empty("xlabel"),
+                attributes: node_attributes(&node.attributes),
+            };
+            node_ids.push(Some(individual.add_node(&spec, cluster)));
+        }
+
+        for edge in parser.edges.iter() {
+            let position = |id: &str| parser.nodes.iter().position(|n| n.id == id).unwrap();
+            // Edges drawn to the border of a cluster, as written by to_dot
+            let cluster = |key: &str| {
+                last_value(&edge.attributes, key)
+                    .and_then(|name| parser.clusters.iter().position(|c| c.name == name))
+                    .map(|c| cluster_ids[c])
+            };
+            let from = cluster("ltail").or(node_ids[position(&edge.from)]);
+            let to = cluster("lhead").or(node_ids[position(&edge.to)]);
+            let (Some(from), Some(to)) = (from, to) else {
+                continue;
+            };
+            let id = individual.upsert_link(from, to, link_style(&edge.attributes));
+            if let Some(label) = last_value(&edge.attributes, "label") {
+                if !label.trim().is_empty() {
+                    individual.links[id].label = Some(label.trim().to_string());
+                }
+            }
+            step.visible_link_ids.insert(id);
+            step.highlighted_link_ids.insert(id);
+        }
+
+        for node in individual.nodes.iter() {
+            step.visible_nodes_ids.insert(node.id);
+            step.highlighted_nodes_ids.insert(node.id);
+        }
+        individual.recompute_descendants_ids();
+        Ok((individual, vec![step]))
+    }
+
+    // Write the graph for Graphviz. Nodes with children become clusters, and
+    // links to them are drawn to the border of the cluster. Descriptions are
+    // external labels. With positions, nodes are pinned where obm placed them,
+    // for rendering with neato -n.
+    pub fn to_dot(&self, positions: bool) -> String {
+        let descendants_ids = self.descendants_ids.as_ref().unwrap();
+        let is_cluster = |id: usize| descendants_ids[id].len() > 1;
+
+        let mut out = String::from("digraph {\n    compound=true;\n");
+        for node in self.nodes.iter().filter(|n| n.parent.is_none()) {
+            self.node_to_dot(node.id, positions, 1, &mut out);
+        }
+        for link in self.links.iter() {
+            let mut attributes = vec![];
+            if is_cluster(link.from) {
+                attributes.push(format!("ltail=cluster_{}", link.from));
+            }
+            if is_cluster(link.to) {
+                attributes.push(format!("lhead=cluster_{}", link.to));
+            }
+            match link.style {
+                LinkStyle::Solid => {}
+                LinkStyle::Dashed => attributes.push("style=dashed".to_string()),
+                LinkStyle::Dotted => attributes.push("style=dotted".to_string()),
+                LinkStyle::Thick => attributes.push("style=bold".to_string()),
+            }
+            if let Some(label) = &link.label {
+                attributes.push(format!("label={}", quoted(label)));
+            }
+            out.push_str(&format!(
+                "    n{} -> n{}{};\n",
+                link.from,
+                link.to,
+                attribute_list(&attributes)
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn node_to_dot(&self, id: usize, positions: bool, depth: usize, out: &mut String) {
+        let node = &self.nodes[id];
+        let indent = "    ".repeat(depth);
+        let label = node.shown_name();
+        let mut position = vec![];
+        if positions {
+            // A cell is 0.1 inch wide and 0.2 inch high, and Graphviz puts the
+            // origin at the bottom left, with positions in points
+            let rect = &node.position;
+            let x = (rect.x as f32 + rect.w as f32 / 2.0) * 7.2;
+            let y = (self.height as f32 - rect.y as f32 - rect.h as f32 / 2.0) * 14.4;
+            position.push(format!("pos=\"{:.1},{:.1}!\"", x, y));
+        }
+
+        let children: Vec<usize> = self
+            .nodes
+            .iter()
+            .filter(|n| n.parent == Some(id))
+            .map(|n| n.id)
+            .collect();
+        if !children.is_empty() {
+            out.push_str(&format!("{}subgraph cluster_{} {{\n", indent, id));
+            out.push_str(&format!("{}    label={};\n", indent, quoted(label)));
+            if let Some(description) = &node.description {
+                out.push_str(&format!("{}    xlabel={};\n", indent, quoted(description)));
+            }
+            let styles = node_styles(node);
+            if !styles.is_empty() {
+                out.push_str(&format!("{}    style={};\n", indent, quoted(&styles)));
+            }
+            // Graphviz has no edges to clusters, they go to this invisible
+            // node and are cut at the border of the cluster
+            let mut anchor = vec!["shape=point".to_string(), "style=invis".to_string()];
+            anchor.extend(position);
+            out.push_str(&format!(
+                "{}    n{}{};\n",
+                indent,
+                id,
+                attribute_list(&anchor)
+            ));
+            for child in children {
+                self.node_to_dot(child, positions, depth + 1, out);
+            }
+            out.push_str(&format!("{}}}\n", indent));
+            return;
+        }
+
+        let mut attributes = vec![format!("label={}", quoted(label))];
+        if let Some(description) = &node.description {
+            attributes.push(format!("xlabel={}", quoted(description)));
+        }
+        attributes.push(match node.shape {
+            Shape::Cylinder => "shape=cylinder".to_string(),
+            _ => "shape=box".to_string(),
+        });
+        let styles = node_styles(node);
+        if !styles.is_empty() {
+            attributes.push(format!("style={}", quoted(&styles)));
+        }
+        if positions {
+            let rect = &node.position;
+            attributes.extend(position);
+            attributes.push(format!("width={:.1}", rect.w as f32 / 10.0));
+            attributes.push(format!("height={:.1}", rect.h as f32 / 5.0));
+            attributes.push("fixedsize=true".to_string());
+        }
+        out.push_str(&format!(
+            "{}n{}{};\n",
+            indent,
+            id,
+            attribute_list(&attributes)
+        ));
+    }
+}
+
+// Rounded corners and dashed borders are both styles in Graphviz
+fn node_styles(node: &Node) -> String {
+    let mut styles = vec![];
+    if node.shape == Shape::Round {
+        styles.push("rounded");
+    }
+    match node.style {
+        BorderStyle::Solid => {}
+        BorderStyle::Dashed => styles.push("dashed"),
+        BorderStyle::Dotted => styles.push("dotted"),
+    }
+    styles.join(",")
+}
+
+fn quoted(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+fn attribute_list(attributes: &[String]) -> String {
+    if attributes.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", attributes.join(", "))
+    }
+}
+
+#[test]
+fn tokenize_test() {
+    let tokens: Vec<Token> = tokenize("digraph { a -> \"b c\" [label=<x <i>y</i>>]; } // end")
+        .unwrap()
+        .into_iter()
+        .map(|l| l.token)
+        .collect();
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Id("digraph".to_string()),
+            Token::OpenBrace,
+            Token::Id("a".to_string()),
+            Token::Arrow,
+            Token::Quoted("b c".to_string()),
+            Token::OpenBracket,
+            Token::Id("label".to_string()),
+            Token::Equal,
+            Token::Id("x <i>y</i>".to_string()),
+            Token::CloseBracket,
+            Token::Semicolon,
+            Token::CloseBrace,
+        ]
+    );
+    assert_eq!(
+        tokenize("digraph {\n  a -> \"b\n").err(),
+        Some(ParseError {
+            line: 2,
+            column: 8,
+            message: "unclosed string".to_string()
+        })
+    );
+}
+
+#[test]
+fn from_dot_test() {
+    let (individual, story) = Individual::from_dot(
+        r#"
+        digraph coffee {
+            node [shape=box];
+            Customer [shape=ellipse];
+            subgraph cluster_cafe {
+                label = "Cafe";
+                Cashier;
+                subgraph cluster_machine {
+                    label = "Espresso Machine";
+                    "Steam Wand"; Cup;
+                }
+                Fridge [label="Refrigerator", shape=cylinder];
+            }
+            /* The order */
+            Customer -> Cashier [label="orders"];
+            Cashier -> Barista -> Cup [style=dashed];
+            Fridge -> "Steam Wand";
+        }
+        "#,
+        80,
+        40,
+    )
+    .unwrap();
+
+    let names: Vec<(&str, Option<usize>)> = individual
+        .nodes
+        .iter()
+        .map(|n| (n.name.as_str(), n.parent))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("Cafe", None),
+            ("Espresso Machine", Some(0)),
+            ("Customer", None),
+            ("Cashier", Some(0)),
+            ("Steam Wand", Some(1)),
+            ("Cup", Some(1)),
+            ("Refrigerator", Some(0)),
+            ("Barista", None),
+        ]
+    );
+    assert_eq!(individual.nodes[2].shape, crate::Shape::Round);
+    assert_eq!(individual.nodes[6].shape, crate::Shape::Cylinder);
+
+    assert_eq!(individual.links.len(), 4);
+    assert_eq!(individual.links[0].label, Some("orders".to_string()));
+    assert_eq!(individual.links[1].style, LinkStyle::Dashed);
+    assert_eq!((individual.links[2].from, individual.links[2].to), (7, 5));
+    assert_eq!(story.len(), 1);
+    assert_eq!(story[0].highlighted_link_ids.len(), 4);
+}
+
+#[test]
+fn from_dot_error_test() {
+    assert_eq!(
+        Individual::from_dot("digraph { a -> ; }", 80, 40).err(),
+        Some(ParseError {
+            line: 1,
+            column: 16,
+            message: "expected an id".to_string()
+        })
+    );
+    assert!(Individual::from_dot("flowchart { a }", 80, 40).is_err());
+    assert_eq!(
+        Individual::from_dot("digraph {\n  \"\" -> b\n}", 80, 40).err(),
+        Some(ParseError {
+            line: 2,
+            column: 3,
+            message: "empty node id".to_string()
+        })
+    );
+    assert_eq!(
+        Individual::from_dot("digraph { a }", 8, 40)
+            .err()
+            .unwrap()
+            .message,
+        "the chart should be larger than 10x10, not 8x40"
+    );
+}
+
+#[test]
+fn from_dot_ids_test() {
+    // Nodes are told apart by their id, not their label
+    let (individual, _) = Individual::from_dot(
+        r#"digraph {
+            subgraph cluster_a { label="Server"; c [label="Cache"] }
+            a [label="Server"]; b [label="Server"]; a -> b; -1 -> -.5 -> b
+        }"#,
+        80,
+        40,
+    )
+    .unwrap();
+    let names: Vec<(&str, Option<usize>)> = individual
+        .nodes
+        .iter()
+        .map(|n| (n.name.as_str(), n.parent))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("Server", None),
+            ("Cache", Some(0)),
+            ("Server", None),
+            ("Server", None),
+            ("-1", None),
+            ("-.5", None),
+        ]
+    );
+    let links: Vec<(usize, usize)> = individual.links.iter().map(|l| (l.from, l.to)).collect();
+    assert_eq!(links, vec![(2, 3), (4, 5), (5, 3)]);
+}
+
+#[test]
+fn to_dot_test() {
+    let (mut individual, _) = Individual::from_string(
+        "Cafe:Barista -> Customer [thirsty \"now\"]\nCustomer ..> Cafe {style=dashed}\nCafe:Fridge {shape=cylinder} -> Cafe:Barista\nCafe [open late] -> Cafe:Barista",
+        80,
+        40,
+    );
+    individual.links[0].label = Some("serves".to_string());
+    assert_eq!(
+        individual.to_dot(false),
+        [
+            "digraph {",
+            "    compound=true;",
+            "    subgraph cluster_0 {",
+            "        label=\"Cafe\";",
+            "        xlabel=\"open late\";",
+            "        style=\"dashed\";",
+            "        n0 [shape=point, style=invis];",
+            "        n1 [label=\"Barista\", shape=box];",
+            "        n3 [label=\"Fridge\", shape=cylinder];",
+            "    }",
+            "    n2 [label=\"Customer\", xlabel=\"thirsty \\\"now\\\"\", shape=box];",
+            "    n1 -> n2 [label=\"serves\"];",
+            "    n2 -> n0 [lhead=cluster_0, style=dotted];",
+            "    n3 -> n1;",
+            "    n0 -> n1 [ltail=cluster_0];",
+            "}",
+            "",
+        ]
+        .join("\n")
+    );
+
+    individual.nodes[2].position = crate::Rectangle {
+        x: 10,
+        y: 20,
+        w: 12,
+        h: 4,
+    };
+    let dot = individual.to_dot(true);
+    assert!(dot.contains("n2 [label=\"Customer\", xlabel=\"thirsty \\\"now\\\"\", shape=box, pos=\"115.2,259.2!\", width=1.2, height=0.8, fixedsize=true];"));
+    assert!(dot.contains("n0 [shape=point, style=invis, pos="));
+
+    // Links to clusters are read back as links to the cluster, and
+    // descriptions as descriptions
+    let (imported, _) = Individual::from_dot(&dot, 80, 40).unwrap();
+    let names: Vec<&str> = imported.nodes.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, vec!["Cafe", "Barista", "Fridge", "Customer"]);
+    assert_eq!(
+        imported.nodes[3].description,
+        Some("thirsty \"now\"".to_string())
+    );
+    assert_eq!(imported.nodes[0].description, Some("open late".to_string()));
+    let links: Vec<(usize, usize)> = imported.links.iter().map(|l| (l.from, l.to)).collect();
+    assert_eq!(links, vec![(1, 3), (3, 0), (2, 1), (0, 1)]);
+    assert_eq!(imported.links[1].style, LinkStyle::Dotted);
+}
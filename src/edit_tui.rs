@@ -0,0 +1,122 @@
+use std::fs;
+use std::io::{self, Write};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+
+use obm::{Editor, Individual, Selection, StoryStep};
+
+#[derive(PartialEq, Debug)]
+enum Command {
+    Select(bool),
+    ToggleLinks,
+    Move(i32, i32),
+    ToggleFixed,
+    ToggleMode,
+    ShiftLink(i32, i32),
+    Improve,
+    Save,
+    Quit,
+    Nothing,
+}
+
+fn command(key: KeyEvent) -> Command {
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Command::Quit,
+        KeyCode::Char('q') | KeyCode::Esc => Command::Quit,
+        KeyCode::Tab => Command::Select(true),
+        KeyCode::BackTab => Command::Select(false),
+        KeyCode::Char('l') => Command::ToggleLinks,
+        KeyCode::Left => Command::Move(-1, 0),
+        KeyCode::Right => Command::Move(1, 0),
+        KeyCode::Up => Command::Move(0, -1),
+        KeyCode::Down => Command::Move(0, 1),
+        KeyCode::Char('f') => Command::ToggleFixed,
+        KeyCode::Char('m') => Command::ToggleMode,
+        KeyCode::Char('[') => Command::ShiftLink(-1, 0),
+        KeyCode::Char(']') => Command::ShiftLink(1, 0),
+        KeyCode::Char('{') => Command::ShiftLink(0, -1),
+        KeyCode::Char('}') => Command::ShiftLink(0, 1),
+        KeyCode::Char('i') => Command::Improve,
+        KeyCode::Char('s') => Command::Save,
+        _ => Command::Nothing,
+    }
+}
+
+fn render(out: &mut impl Write, editor: &Editor, message: &str) -> io::Result<()> {
+    queue!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    for line in editor.render().lines() {
+        write!(out, "{}\r\n", line)?;
+    }
+    let help = match editor.selection {
+        Selection::Node(_) => "tab select, arrows move, f fix, l links",
+        Selection::Link(_) => "tab select, m mode, [ ] start, { } end, f fix, l nodes",
+    };
+    write!(
+        out,
+        "\r\n{}\r\n{}, i improve, s save, q quit {}",
+        editor.status(),
+        help,
+        message
+    )?;
+    out.flush()
+}
+
+// Move nodes and links by hand, full screen, then save the layout as json to
+// `path`. Fixed nodes and links are kept as they are when improving the rest.
+pub fn edit(world: Individual, story: &[StoryStep], path: &str) -> io::Result<()> {
+    let mut editor = Editor::new(world);
+    if editor.is_empty() {
+        return Ok(());
+    }
+    let mut out = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    let result = (|| {
+        let mut message = String::new();
+        loop {
+            render(&mut out, &editor, &message)?;
+            message.clear();
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            match command(key) {
+                Command::Select(forward) => editor.select_next(forward),
+                Command::ToggleLinks => editor.toggle_links(),
+                Command::Move(dx, dy) => {
+                    if !editor.move_selected(dx, dy) {
+                        message = "(can't move there)".to_string();
+                    }
+                }
+                Command::ToggleFixed => editor.toggle_fixed(),
+                Command::ToggleMode => editor.toggle_mode(),
+                Command::ShiftLink(start, end) => editor.shift_link(start, end),
+                Command::Improve => editor.improve(),
+                Command::Save => {
+                    fs::write(path, editor.world.to_json(story))?;
+                    message = format!("(saved to {})", path);
+                }
+                Command::Quit => return Ok(()),
+                Command::Nothing => {}
+            }
+        }
+    })();
+
+    execute!(out, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+#[test]
+fn command_test() {
+    let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+    assert_eq!(command(key(KeyCode::Left)), Command::Move(-1, 0));
+    assert_eq!(command(key(KeyCode::BackTab)), Command::Select(false));
+    assert_eq!(command(key(KeyCode::Char('}'))), Command::ShiftLink(0, 1));
+    assert_eq!(command(key(KeyCode::Char('x'))), Command::Nothing);
+}
@@ -0,0 +1,197 @@
+use std::collections::HashSet;
+
+use crate::{Individual, StoryStep, GRID_HEIGHT, GRID_WIDTH};
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Selection {
+    Node(usize),
+    Link(usize),
+}
+
+// Changes a layout by hand, node by node and link by link. The terminal editor
+// of the binary is a thin layer over this.
+pub struct Editor {
+    pub world: Individual,
+    pub selection: Selection,
+}
+
+impl Editor {
+    pub fn new(world: Individual) -> Editor {
+        Editor {
+            world,
+            selection: Selection::Node(0),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.world.nodes.is_empty()
+    }
+
+    // Go through the nodes, or the links, in the order of their ids
+    pub fn select_next(&mut self, forward: bool) {
+        let step = |id: usize, count: usize| {
+            if count == 0 {
+                0
+            } else if forward {
+                (id + 1) % count
+            } else {
+                (id + count - 1) % count
+            }
+        };
+        self.selection = match self.selection {
+            Selection::Node(id) => Selection::Node(step(id, self.world.nodes.len())),
+            Selection::Link(id) => Selection::Link(step(id, self.world.links.len())),
+        };
+    }
+
+    // Switch between selecting nodes and selecting links
+    pub fn toggle_links(&mut self) {
+        self.selection = match self.selection {
+            Selection::Node(_) if !self.world.links.is_empty() => Selection::Link(0),
+            Selection::Node(id) => Selection::Node(id),
+            Selection::Link(_) => Selection::Node(0),
+        };
+    }
+
+    // Move the selected node with everything inside it by whole grid cells.
+    // Returns false when that would push a node out of the chart.
+    pub fn move_selected(&mut self, dx: i32, dy: i32) -> bool {
+        let Selection::Node(id) = self.selection else {
+            return false;
+        };
+        let Some(descendants) = self.world.descendants_ids.as_ref() else {
+            return false;
+        };
+        let ids = descendants[id].clone();
+        let (dx, dy) = (dx * GRID_WIDTH, dy * GRID_HEIGHT);
+        let fits = ids.iter().all(|id| {
+            let position = &self.world.nodes[*id].position;
+            position.x + dx >= 0
+                && position.y + dy >= 0
+                && position.x + dx + position.w <= self.world.width
+                && position.y + dy + position.h <= self.world.height
+        });
+        if fits {
+            for id in ids {
+                self.world.nodes[id].position.x += dx;
+                self.world.nodes[id].position.y += dy;
+            }
+        }
+        fits
+    }
+
+    // A fixed node or link is left alone by improve
+    pub fn toggle_fixed(&mut self) {
+        match self.selection {
+            Selection::Node(id) => self.world.nodes[id].fixed = !self.world.nodes[id].fixed,
+            Selection::Link(id) => self.world.links[id].fixed = !self.world.links[id].fixed,
+        }
+    }
+
+    // Whether the link goes horizontally or vertically first
+    pub fn toggle_mode(&mut self) {
+        if let Selection::Link(id) = self.selection {
+            self.world.links[id].mode = !self.world.links[id].mode;
+        }
+    }
+
+    // Slide where the link leaves its first node, or reaches its second one,
+    // along the border of the node
+    pub fn shift_link(&mut self, start: i32, end: i32) {
+        if let Selection::Link(id) = self.selection {
+            self.world.links[id].start += start;
+            self.world.links[id].end += end;
+        }
+    }
+
+    pub fn improve(&mut self) {
+        self.world.improve();
+    }
+
+    // The whole chart, with the selection highlighted. When a link is
+    // selected, it is the only one drawn.
+    pub fn render(&self) -> String {
+        let mut step = StoryStep {
+            visible_nodes_ids: (0..self.world.nodes.len()).collect(),
+            visible_link_ids: (0..self.world.links.len()).collect(),
+            ..Default::default()
+        };
+        match self.selection {
+            Selection::Node(id) => {
+                step.highlighted_nodes_ids = HashSet::from([id]);
+                step.highlighted_link_ids = step.visible_link_ids.clone();
+            }
+            Selection::Link(id) => {
+                let link = &self.world.links[id];
+                step.highlighted_nodes_ids = HashSet::from([link.from, link.to]);
+                step.highlighted_link_ids = HashSet::from([id]);
+            }
+        }
+        self.world.to_string(&step)
+    }
+
+    // One line about the selection and the score
+    pub fn status(&self) -> String {
+        let fixed = |fixed: bool| if fixed { ", fixed" } else { "" };
+        let selection = match self.selection {
+            Selection::Node(id) => {
+                let node = &self.world.nodes[id];
+                format!("node {}{}", node.name, fixed(node.fixed))
+            }
+            Selection::Link(id) => {
+                let link = &self.world.links[id];
+                format!(
+                    "link {} -> {}, {} first, start {}, end {}{}",
+                    self.world.nodes[link.from].name,
+                    self.world.nodes[link.to].name,
+                    if link.mode { "vertical" } else { "horizontal" },
+                    link.start,
+                    link.end,
+                    fixed(link.fixed)
+                )
+            }
+        };
+        format!("{} | score {}", selection, self.world.score().0)
+    }
+}
+
+#[test]
+fn editor_test() {
+    let (mut world, _) = Individual::from_string("Cafe:Barista -> Customer", 80, 40);
+    world.nodes[0].position.x = 6;
+    world.nodes[0].position.y = 4;
+    world.nodes[1].position.x = 12;
+    world.nodes[1].position.y = 8;
+    let mut editor = Editor::new(world);
+
+    // Moving a parent moves what is inside it
+    assert!(editor.move_selected(1, 1));
+    assert_eq!(editor.world.nodes[0].position.x, 6 + GRID_WIDTH);
+    assert_eq!(editor.world.nodes[1].position.x, 12 + GRID_WIDTH);
+    assert_eq!(editor.world.nodes[1].position.y, 8 + GRID_HEIGHT);
+    assert!(!editor.move_selected(-100, 0));
+    assert_eq!(editor.world.nodes[1].position.x, 12 + GRID_WIDTH);
+
+    editor.select_next(false);
+    assert_eq!(editor.selection, Selection::Node(2));
+    editor.toggle_fixed();
+    assert!(editor.world.nodes[2].fixed);
+    assert!(editor.status().starts_with("node Customer, fixed | score "));
+
+    editor.toggle_links();
+    assert_eq!(editor.selection, Selection::Link(0));
+    let mode = editor.world.links[0].mode;
+    editor.toggle_mode();
+    editor.shift_link(1, -1);
+    assert_eq!(editor.world.links[0].mode, !mode);
+    assert_eq!(
+        (editor.world.links[0].start, editor.world.links[0].end),
+        (1, -1)
+    );
+    assert!(editor.status().starts_with("link Barista -> Customer"));
+
+    // Fixed nodes stay where they are
+    let position = editor.world.nodes[2].position.clone();
+    editor.improve();
+    assert_eq!(editor.world.nodes[2].position, position);
+}
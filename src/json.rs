@@ -0,0 +1,379 @@
+use serde::de::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    size_error, stops_of_link, BorderStyle, Changes, Individual, Link, LinkStyle, Node, Point,
+    Rectangle, Shape, StoryStep,
+};
+
+// The computed geometry of a chart, so that other tools can render or
+// post-process obm layouts. When reading a layout back, the fields that are
+// computed from the others (score, depth, envelope, points) can be left out.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Layout {
+    width: i32,
+    height: i32,
+    #[serde(default)]
+    score: i32,
+    #[serde(default)]
+    fit_to_grid: bool,
+    nodes: Vec<NodeLayout>,
+    links: Vec<LinkLayout>,
+    steps: Vec<StepLayout>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct NodeLayout {
+    id: usize,
+    name: String,
+    #[serde(default)]
+    display_name: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    parent: Option<usize>,
+    #[serde(default)]
+    depth: usize,
+    #[serde(default)]
+    shape: Shape,
+    #[serde(default)]
+    style: BorderStyle,
+    #[serde(default)]
+    fixed: bool,
+    // Where the name is written
+    rect: Rectangle,
+    // The border drawn around the node and its children
+    #[serde(default)]
+    envelope: Rectangle,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct LinkLayout {
+    id: usize,
+    from: usize,
+    to: usize,
+    #[serde(default)]
+    style: LinkStyle,
+    start: i32,
+    end: i32,
+    mode: bool,
+    #[serde(default)]
+    fixed: bool,
+    #[serde(default)]
+    label: Option<String>,
+    // The corners of the link, from the border of "from" to the border of "to"
+    #[serde(default)]
+    points: Vec<Point>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct StepLayout {
+    visible_nodes: Vec<usize>,
+    visible_links: Vec<usize>,
+    highlighted_nodes: Vec<usize>,
+    highlighted_links: Vec<usize>,
+    #[serde(default)]
+    dim_others: bool,
+    // Only there when showing changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    new_nodes: Option<Vec<usize>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    new_links: Option<Vec<usize>>,
+    #[serde(default)]
+    md: String,
+}
+
+fn sorted<'a>(ids: impl Iterator<Item = &'a usize>) -> Vec<usize> {
+    let mut ids: Vec<usize> = ids.copied().collect();
+    ids.sort();
+    ids
+}
+
+impl Individual {
+    fn layout(&self, story: &[StoryStep]) -> Layout {
+        let envelopes = self.envelopes();
+        Layout {
+            width: self.width,
+            height: self.height,
+            score: self.score().0,
+            fit_to_grid: self.fit_to_grid,
+            nodes: self
+                .nodes
+                .iter()
+                .map(|node| NodeLayout {
+                    id: node.id,
+                    name: node.name.clone(),
+                    display_name: node.display_name.clone(),
+                    description: node.description.clone(),
+                    parent: node.parent,
+                    depth: node.depth,
+                    shape: node.shape,
+                    style: node.style,
+                    fixed: node.fixed,
+                    rect: node.position.clone(),
+                    envelope: envelopes[node.id].clone(),
+                })
+                .collect(),
+            links: self
+                .links
+                .iter()
+                .map(|link| LinkLayout {
+                    id: link.id,
+                    from: link.from,
+                    to: link.to,
+                    style: link.style,
+                    start: link.start,
+                    end: link.end,
+                    mode: link.mode,
+                    fixed: link.fixed,
+                    label: link.label.clone(),
+                    points: stops_of_link(&envelopes[link.from], &envelopes[link.to], link).1,
+                })
+                .collect(),
+            steps: story
+                .iter()
+                .map(|step| StepLayout {
+                    visible_nodes: sorted(step.visible_nodes_ids.iter()),
+                    visible_links: sorted(step.visible_link_ids.iter()),
+                    highlighted_nodes: sorted(step.highlighted_nodes_ids.iter()),
+                    highlighted_links: sorted(step.highlighted_link_ids.iter()),
+                    dim_others: step.dim_others,
+                    new_nodes: step
+                        .changes
+                        .as_ref()
+                        .map(|c| sorted(c.new_nodes_ids.iter())),
+                    new_links: step.changes.as_ref().map(|c| sorted(c.new_link_ids.iter())),
+                    md: step.md.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_json(&self, story: &[StoryStep]) -> String {
+        serde_json::to_string_pretty(&self.layout(story)).unwrap()
+    }
+
+    // Load a layout saved with to_json, as is. Nothing is optimized, so that a
+    // hand tuned layout renders the same way every time.
+    pub fn from_json(json: &str) -> Result<(Individual, Vec<StoryStep>), serde_json::Error> {
+        let layout: Layout = serde_json::from_str(json)?;
+        if let Some(error) = size_error(layout.width, layout.height) {
+            return Err(Error::custom(error.message));
+        }
+
+        let mut nodes: Vec<Node> = vec![];
+        for (index, node) in layout.nodes.into_iter().enumerate() {
+            if node.id != index {
+                return Err(Error::custom(format!(
+                    "node {} should have the id {}",
+                    node.name, index
+                )));
+            }
+            let depth = match node.parent {
+                Some(parent) if parent < index => nodes[parent].depth + 1,
+                Some(parent) => {
+                    return Err(Error::custom(format!(
+                        "the parent {} of node {} should come before it",
+                        parent, node.name
+                    )))
+                }
+                None => 0,
+            };
+            nodes.push(Node {
+                id: index,
+                parent: node.parent,
+                depth,
+                position: node.rect,
+                name: node.name,
+                display_name: node.display_name,
+                description: node.description,
+                shape: node.shape,
+                style: node.style,
+                fixed: node.fixed,
+            });
+        }
+
+        let node_exists = |id: &usize| *id < nodes.len();
+        let mut links: Vec<Link> = vec![];
+        for (index, link) in layout.links.into_iter().enumerate() {
+            if link.id != index {
+                return Err(Error::custom(format!(
+                    "link {} should have the id {}",
+                    link.id, index
+                )));
+            }
+            if !node_exists(&link.from) || !node_exists(&link.to) {
+                return Err(Error::custom(format!("link {} joins unknown nodes", index)));
+            }
+            links.push(Link {
+                id: index,
+                from: link.from,
+                to: link.to,
+                start: link.start,
+                end: link.end,
+                fixed: link.fixed,
+                mode: link.mode,
+                style: link.style,
+                label: link.label,
+            });
+        }
+
+        let link_exists = |id: &usize| *id < links.len();
+        let mut story = vec![];
+        for (index, step) in layout.steps.into_iter().enumerate() {
+            if !step
+                .visible_nodes
+                .iter()
+                .chain(&step.highlighted_nodes)
+                .chain(step.new_nodes.iter().flatten())
+                .all(node_exists)
+                || !step
+                    .visible_links
+                    .iter()
+                    .chain(&step.highlighted_links)
+                    .chain(step.new_links.iter().flatten())
+                    .all(link_exists)
+            {
+                return Err(Error::custom(format!("step {} shows unknown ids", index)));
+            }
+            story.push(StoryStep {
+                visible_nodes_ids: step.visible_nodes.into_iter().collect(),
+                visible_link_ids: step.visible_links.into_iter().collect(),
+                highlighted_nodes_ids: step.highlighted_nodes.into_iter().collect(),
+                highlighted_link_ids: step.highlighted_links.into_iter().collect(),
+                md: step.md,
+                dim_others: step.dim_others,
+                changes: match (step.new_nodes, step.new_links) {
+                    (None, None) => None,
+                    (new_nodes, new_links) => Some(Changes {
+                        new_nodes_ids: new_nodes.unwrap_or_default().into_iter().collect(),
+                        new_link_ids: new_links.unwrap_or_default().into_iter().collect(),
+                    }),
+                },
+            });
+        }
+
+        let mut individual = Individual {
+            nodes,
+            links,
+            width: layout.width,
+            height: layout.height,
+            descendants_ids: None,
+            fit_to_grid: layout.fit_to_grid,
+        };
+        individual.recompute_descendants_ids();
+        Ok((individual, story))
+    }
+}
+
+#[test]
+fn to_json_test() {
+    let (mut individual, story) = Individual::from_string(
+        "Cafe:Barista -> Customer [thirsty]\nserves a coffee\nCustomer ..> Cafe",
+        80,
+        40,
+    );
+    individual.nodes[1].position = Rectangle {
+        x: 6,
+        y: 4,
+        w: 11,
+        h: 3,
+    };
+    individual.nodes[2].position.x = 30;
+    individual.nodes[2].position.y = 12;
+    let json: serde_json::Value = serde_json::from_str(&individual.to_json(&story)).unwrap();
+
+    assert_eq!(json["width"], 80);
+    assert_eq!(json["nodes"].as_array().unwrap().len(), 3);
+    assert_eq!(json["nodes"][1]["name"], "Barista");
+    assert_eq!(json["nodes"][1]["parent"], 0);
+    assert_eq!(json["nodes"][1]["shape"], "box");
+    assert_eq!(
+        json["nodes"][1]["rect"],
+        serde_json::json!({"x": 6, "y": 4, "w": 11, "h": 3})
+    );
+    assert_eq!(json["nodes"][2]["description"], "thirsty");
+    assert_eq!(json["links"][1]["style"], "dotted");
+    assert!(json["links"][0]["points"].as_array().unwrap().len() >= 2);
+    assert_eq!(
+        json["steps"][0]["visible_nodes"],
+        serde_json::json!([0, 1, 2])
+    );
+    assert_eq!(json["steps"][0]["md"], "serves a coffee\n");
+    assert_eq!(
+        json["steps"][1]["highlighted_links"],
+        serde_json::json!([1])
+    );
+}
+
+#[test]
+fn from_json_round_trip_test() {
+    let (mut individual, story) =
+        Individual::from_string(include_str!("../samples/coffee.md"), 80, 40);
+    individual.improve();
+
+    let (imported, imported_story) = Individual::from_json(&individual.to_json(&story)).unwrap();
+    assert_eq!(imported, individual);
+    assert_eq!(imported_story.len(), story.len());
+    for (step, imported_step) in story.iter().zip(imported_story.iter()) {
+        assert_eq!(
+            individual.to_string(step),
+            imported.to_string(imported_step)
+        );
+        assert_eq!(step.md, imported_step.md);
+    }
+}
+
+#[test]
+fn from_json_hand_written_test() {
+    let (individual, story) = Individual::from_json(
+        r#"{
+            "width": 40,
+            "height": 20,
+            "nodes": [
+                {"id": 0, "name": "Cafe", "rect": {"x": 2, "y": 2, "w": 8, "h": 3}},
+                {"id": 1, "name": "Barista", "parent": 0, "rect": {"x": 3, "y": 6, "w": 11, "h": 3}}
+            ],
+            "links": [],
+            "steps": [{
+                "visible_nodes": [0, 1],
+                "visible_links": [],
+                "highlighted_nodes": [1],
+                "highlighted_links": [],
+                "md": "Hello\n"
+            }]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(individual.nodes[1].depth, 1);
+    assert_eq!(
+        individual.to_string(&story[0]),
+        [
+            "    ┌───────────┐",
+            "    │ Cafe      │",
+            "    │           │",
+            "    │           │",
+            "    │╔═════════╗│",
+            "    │║ Barista ║│",
+            "    │╚═════════╝│",
+            "    └───────────┘\n",
+        ]
+        .join("\n")
+    );
+
+    assert!(Individual::from_json(r#"{"width": 40}"#).is_err());
+    assert!(Individual::from_json(
+        r#"{"width": 40, "height": -3, "nodes": [], "links": [], "steps": []}"#
+    )
+    .err()
+    .unwrap()
+    .to_string()
+    .starts_with("the chart should be larger than 10x10"));
+    assert!(Individual::from_json(
+        r#"{"width": 40, "height": 20, "links": [], "steps": [], "nodes": [
+            {"id": 0, "name": "Barista", "parent": 1, "rect": {"x": 3, "y": 6, "w": 11, "h": 3}},
+            {"id": 1, "name": "Cafe", "rect": {"x": 2, "y": 2, "w": 8, "h": 3}}
+        ]}"#
+    )
+    .is_err());
+}
@@ -1,13 +1,30 @@
 extern crate console_error_panic_hook;
 
+mod animation;
+mod canvas;
+mod document;
+mod dot;
+mod editor;
+mod json;
+mod lint;
+mod mermaid;
+mod simplify;
+mod slides;
+
 use std::cmp::{max, min};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::ops::Deref;
 
 use rand::prelude::*;
-use random;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+use canvas::{display_width, wrap, Canvas};
+use document::{document_lines, DocumentOptions, Line};
+pub use editor::{Editor, Selection};
+pub use lint::{lint, Warning};
+
 use Direction::{Down, Left, Right, Up};
 use Mutation::*;
 
@@ -15,6 +32,9 @@ const GRID_WIDTH: i32 = 6;
 
 const GRID_HEIGHT: i32 = 4;
 
+// Names and descriptions wider than this wrap on several lines
+const LABEL_MAX_WIDTH: i32 = 24;
+
 pub struct RulesParams {
     individual: Individual,
     descendants_ids: Vec<Vec<usize>>,
@@ -24,7 +44,9 @@ pub struct RulesParams {
     nodes_scores: Vec<i32>,
 }
 
-fn get_rules() -> Vec<(&'static str, i32, Box<dyn Fn(&mut RulesParams) -> i32>)> {
+type Rule = (&'static str, i32, Box<dyn Fn(&mut RulesParams) -> i32>);
+
+fn get_rules() -> Vec<Rule> {
     vec![
         (
             "avoid name/name overlap",
@@ -76,7 +98,7 @@ fn get_rules() -> Vec<(&'static str, i32, Box<dyn Fn(&mut RulesParams) -> i32>)>
                     for (id, borders) in rp.envelope_borders.iter().enumerate() {
                         if id != a.id {
                             for border in borders {
-                                let delta = &a.position.overlap_with(&border);
+                                let delta = &a.position.overlap_with(border);
                                 rp.nodes_scores[a.id] += delta;
                                 rp.nodes_scores[id] += delta;
                                 t += delta;
@@ -222,7 +244,7 @@ fn get_rules() -> Vec<(&'static str, i32, Box<dyn Fn(&mut RulesParams) -> i32>)>
                         if link_b_index > link_a_index {
                             for link_a_part in rects_a {
                                 for link_b_part in rects_b {
-                                    let delta = &link_a_part.overlap_with(&link_b_part);
+                                    let delta = &link_a_part.overlap_with(link_b_part);
 
                                     rp.nodes_scores[link_a.from] += delta;
                                     rp.nodes_scores[link_a.to] += delta;
@@ -469,13 +491,13 @@ fn walk_rectangle_perimeter(rect: &Rectangle, walk: i32) -> (Direction, Point) {
     }
     let walk = walk - rect.w;
 
-    return (
+    (
         Left,
         Point {
             x: rect.x - 1,
             y: rect.y + rect.h - 1 - walk,
         },
-    );
+    )
 }
 
 #[test]
@@ -514,12 +536,64 @@ fn stops_of_link(
     to: &Rectangle,
     link: &Link,
 ) -> (Direction, Vec<Point>, Direction) {
-    let (start_dir, start) = walk_rectangle_perimeter(&from, link.start);
-    let (end_dir, end) = walk_rectangle_perimeter(&to, link.end);
+    let (start_dir, start) = walk_rectangle_perimeter(from, link.start);
+    let (end_dir, end) = walk_rectangle_perimeter(to, link.end);
     let center = transition(&start, &end, link.mode);
     let mut stops = vec![start, center, end];
     stops.dedup();
-    return (start_dir, stops, end_dir.flip());
+    (start_dir, stops, end_dir.flip())
+}
+
+// Where to write the label of a link : over the middle of the longest
+// horizontal part if it is long enough, otherwise next to the longest vertical
+// part. Also returns the text to write, padded with spaces.
+fn link_label_position(stops: &[Point], label: &str) -> (Point, String) {
+    let text = format!(" {} ", label);
+    let width = display_width(&text);
+    let longest = |horizontal: bool| {
+        stops
+            .windows(2)
+            .filter(|pair| (pair[0].y == pair[1].y) == horizontal)
+            .max_by_key(|pair| (pair[0].x - pair[1].x).abs() + (pair[0].y - pair[1].y).abs())
+    };
+    if let Some([a, b]) = longest(true) {
+        // Keep the corners at both ends of the segment
+        if (a.x - b.x).abs() > width {
+            let x = (a.x + b.x) / 2 - width / 2;
+            return (Point { x, y: a.y }, text);
+        }
+    }
+    match longest(false) {
+        Some([a, b]) => (
+            Point {
+                x: a.x,
+                y: (a.y + b.y) / 2,
+            },
+            text,
+        ),
+        // A single point link, likely between two boxes that touch
+        _ => (stops[0].clone(), text),
+    }
+}
+
+#[test]
+fn link_label_position_test() {
+    let stops = vec![
+        Point { x: 0, y: 0 },
+        Point { x: 20, y: 0 },
+        Point { x: 20, y: 4 },
+    ];
+    assert_eq!(
+        link_label_position(&stops, "calls"),
+        (Point { x: 7, y: 0 }, " calls ".to_string())
+    );
+    assert_eq!(
+        link_label_position(&stops, "a label too long to fit"),
+        (
+            Point { x: 20, y: 2 },
+            " a label too long to fit ".to_string()
+        )
+    );
 }
 
 fn overlap_1d(x1: i32, w1: i32, x2: i32, w2: i32) -> i32 {
@@ -535,7 +609,7 @@ fn overlap_1d(x1: i32, w1: i32, x2: i32, w2: i32) -> i32 {
     if x2 >= x1 && x2 <= x1 + w1 && x2 + w2 >= x1 + w1 {
         return x1 + w1 - x2;
     }
-    return w2;
+    w2
 }
 
 #[test]
@@ -548,7 +622,7 @@ fn overlap_1d_test() {
 }
 
 fn overlap(r1: &Rectangle, r2: &Rectangle) -> i32 {
-    return overlap_1d(r1.x, r1.w, r2.x, r2.w) * overlap_1d(r1.y, r1.h, r2.y, r2.h);
+    overlap_1d(r1.x, r1.w, r2.x, r2.w) * overlap_1d(r1.y, r1.h, r2.y, r2.h)
 }
 
 #[test]
@@ -623,6 +697,7 @@ fn overlap_test() {
     );
 }
 
+#[allow(dead_code)]
 fn overlap_padded(r1: &Rectangle, r2: &Rectangle, padding: i32) -> i32 {
     assert!(padding >= 0);
     overlap_1d(
@@ -680,7 +755,7 @@ fn overlap_padded_test() {
     assert_eq!(overlap_1d(-1, 2, -1, 2), 2);
 }
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
 
 pub struct Rectangle {
     x: i32,
@@ -716,11 +791,11 @@ impl Rectangle {
     fn overlap_with(&self, target: &Rectangle) -> i32 {
         overlap(self, target)
     }
+    #[allow(dead_code)]
     fn overlaps(&self, target: &Rectangle) -> bool {
         self.overlap_with(target) > 0
     }
     fn gobble_up(&self, target: &Rectangle) -> Rectangle {
-        //  Follow grid here ?
         let x = [
             self.x,
             self.x + self.w - 1,
@@ -744,6 +819,19 @@ impl Rectangle {
             },
         )
     }
+    // Grow the rectangle until its borders sit on grid lines
+    fn snapped_to_grid(&self) -> Rectangle {
+        Rectangle::from_points(
+            &Point {
+                x: floor_to_grid_line(self.x, GRID_WIDTH),
+                y: floor_to_grid_line(self.y, GRID_HEIGHT),
+            },
+            &Point {
+                x: ceil_to_grid_line(self.x + self.w - 1, GRID_WIDTH),
+                y: ceil_to_grid_line(self.y + self.h - 1, GRID_HEIGHT),
+            },
+        )
+    }
     fn from_points(p1: &Point, p2: &Point) -> Self {
         let (x, w) = if p1.x < p2.x {
             (p1.x, p2.x - p1.x + 1)
@@ -763,6 +851,7 @@ impl Rectangle {
             y: self.y + self.h / 2,
         }
     }
+    #[allow(dead_code)]
     fn shift_by(&self, p: &Point) -> Rectangle {
         Rectangle {
             x: self.x + p.x,
@@ -782,24 +871,28 @@ impl Rectangle {
             y: self.y + self.h - 1,
         }
     }
+    #[allow(dead_code)]
     fn left(&self) -> Point {
         Point {
             x: self.x - 1,
             y: self.y + self.h / 2,
         }
     }
+    #[allow(dead_code)]
     fn top(&self) -> Point {
         Point {
             x: self.x + self.w / 2,
             y: self.y - 1,
         }
     }
+    #[allow(dead_code)]
     fn right(&self) -> Point {
         Point {
             x: self.x + self.w,
             y: self.y + self.h / 2,
         }
     }
+    #[allow(dead_code)]
     fn bottom(&self) -> Point {
         Point {
             x: self.x + self.w / 2,
@@ -807,6 +900,7 @@ impl Rectangle {
         }
     }
 
+    #[allow(dead_code)]
     fn size(&self) -> i32 {
         self.h * 2 * self.w
     }
@@ -880,7 +974,7 @@ fn rectangle_borders() {
     )
 }
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 
 pub struct Point {
     x: i32,
@@ -895,10 +989,48 @@ fn fit_on_y_grid(position: i32) -> i32 {
     (position / GRID_HEIGHT) * GRID_HEIGHT
 }
 
+// Height rounded up to end on a grid line, so boxes below a tall one stay
+// aligned on the grid. Rounded like widths, the gap line below the box being
+// the grid line.
+fn fit_height_on_grid(height: i32) -> i32 {
+    ((height + GRID_HEIGHT) / GRID_HEIGHT) * GRID_HEIGHT - 1
+}
+
+fn fit_width_on_grid(width: i32) -> i32 {
+    ((width + GRID_WIDTH) / GRID_WIDTH) * GRID_WIDTH - 1
+}
+
+// Grid lines are the last column (or row) of each grid cell, the gap left
+// after a box that was fitted on the grid
+fn floor_to_grid_line(position: i32, grid: i32) -> i32 {
+    (position + 1).div_euclid(grid) * grid - 1
+}
+
+fn ceil_to_grid_line(position: i32, grid: i32) -> i32 {
+    (position + grid).div_euclid(grid) * grid - 1
+}
+
+#[test]
+fn grid_lines_test() {
+    assert_eq!(fit_width_on_grid(8), 11);
+    assert_eq!(fit_width_on_grid(11), 11);
+    assert_eq!(fit_height_on_grid(3), 3);
+    assert_eq!(fit_height_on_grid(4), 7);
+    assert_eq!(fit_height_on_grid(7), 7);
+    assert_eq!(fit_height_on_grid(8), 11);
+    assert_eq!(floor_to_grid_line(5, 6), 5);
+    assert_eq!(floor_to_grid_line(4, 6), -1);
+    assert_eq!(floor_to_grid_line(-2, 6), -7);
+    assert_eq!(ceil_to_grid_line(5, 6), 5);
+    assert_eq!(ceil_to_grid_line(6, 6), 11);
+    assert_eq!(ceil_to_grid_line(-2, 6), -1);
+}
+
 impl Point {
     fn d2(&self, b: &Point) -> i32 {
         (self.x - b.x).pow(2) + ((self.y - b.y) * 2).pow(2)
     }
+    #[allow(dead_code)]
     fn dabs(&self, b: &Point) -> i32 {
         (self.x - b.x).abs() + (self.y - b.y).abs() * 2
     }
@@ -936,18 +1068,76 @@ pub struct Individual {
     width: i32,
     height: i32,
     descendants_ids: Option<Vec<Vec<usize>>>,
+    fit_to_grid: bool,
 }
 
+#[derive(Default)]
 pub struct StoryStep {
     visible_nodes_ids: HashSet<usize>,
     visible_link_ids: HashSet<usize>,
 
     highlighted_nodes_ids: HashSet<usize>,
     highlighted_link_ids: HashSet<usize>,
+    // Set by !focus, draws what isn't highlighted with light dotted borders
+    dim_others: bool,
+    // What this step shows for the first time, when showing changes
+    changes: Option<Changes>,
 
     pub md: String,
 }
 
+#[derive(Default, Clone, PartialEq, Debug)]
+struct Changes {
+    new_nodes_ids: HashSet<usize>,
+    new_link_ids: HashSet<usize>,
+}
+
+// Diff the visible nodes and links of consecutive steps, so that each step
+// draws what appears for the first time apart from what was already there
+pub fn mark_changes(story: &mut [StoryStep]) {
+    let mut previous = StoryStep::default();
+    for step in story.iter_mut() {
+        step.changes = Some(Changes {
+            new_nodes_ids: step
+                .visible_nodes_ids
+                .difference(&previous.visible_nodes_ids)
+                .copied()
+                .collect(),
+            new_link_ids: step
+                .visible_link_ids
+                .difference(&previous.visible_link_ids)
+                .copied()
+                .collect(),
+        });
+        previous.visible_nodes_ids = step.visible_nodes_ids.clone();
+        previous.visible_link_ids = step.visible_link_ids.clone();
+    }
+}
+
+impl StoryStep {
+    fn hide_link(&mut self, id: usize) {
+        self.visible_link_ids.remove(&id);
+        self.highlighted_link_ids.remove(&id);
+    }
+}
+
+// Start a new step, showing what the previous one showed, unless the previous
+// one has no text yet
+fn next_step(story: &mut Vec<StoryStep>) {
+    let current_story = story.last().unwrap();
+    if !current_story.md.is_empty() {
+        story.push(StoryStep {
+            visible_nodes_ids: current_story.visible_nodes_ids.clone(),
+            visible_link_ids: current_story.visible_link_ids.clone(),
+            highlighted_nodes_ids: HashSet::new(),
+            highlighted_link_ids: HashSet::new(),
+            md: String::new(),
+            dim_others: false,
+            changes: None,
+        })
+    }
+}
+
 #[derive(PartialEq, Debug, Clone)]
 
 pub struct Node {
@@ -956,25 +1146,323 @@ pub struct Node {
     depth: usize,
     position: Rectangle,
     name: String,
+    // Drawn instead of the name, set by an alias
+    display_name: Option<String>,
+    description: Option<String>,
+    shape: Shape,
+    style: BorderStyle,
     fixed: bool,
 }
+
+#[derive(PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shape {
+    #[default]
+    Box,
+    Round,
+    Cylinder,
+}
+
+impl Shape {
+    fn from_name(name: &str) -> Option<Shape> {
+        match name {
+            "box" | "rect" | "rectangle" => Some(Shape::Box),
+            "round" => Some(Shape::Round),
+            "cylinder" => Some(Shape::Cylinder),
+            _ => None,
+        }
+    }
+    // Rows drawn between the top border and the label, like the lid of a cylinder
+    fn header_rows(&self) -> i32 {
+        match self {
+            Shape::Cylinder => 1,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BorderStyle {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl BorderStyle {
+    fn from_name(name: &str) -> Option<BorderStyle> {
+        match name {
+            "solid" => Some(BorderStyle::Solid),
+            "dashed" => Some(BorderStyle::Dashed),
+            "dotted" => Some(BorderStyle::Dotted),
+            _ => None,
+        }
+    }
+}
+
+impl Node {
+    // The name drawn in the box, which is not always the one used in paths
+    fn shown_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.name)
+    }
+
+    // Lines of text drawn in the box, the wrapped name then the description
+    fn label(&self) -> Vec<String> {
+        let mut lines = wrap(self.shown_name(), LABEL_MAX_WIDTH);
+        if let Some(description) = &self.description {
+            lines.extend(wrap(description, LABEL_MAX_WIDTH));
+        }
+        lines
+    }
+
+    // Width and height of the box needed to fit the label
+    fn label_size(&self) -> (i32, i32) {
+        let label = self.label();
+        let w = label.iter().map(|l| display_width(l)).max().unwrap_or(0) + 4;
+        let h = fit_height_on_grid(label.len() as i32 + 2 + self.shape.header_rows());
+        (w, h)
+    }
+
+    fn apply_attributes(&mut self, attributes: &[(&str, &str)]) {
+        for (key, value) in attributes {
+            match *key {
+                "shape" => self.shape = Shape::from_name(value).unwrap_or(self.shape),
+                "style" => self.style = BorderStyle::from_name(value).unwrap_or(self.style),
+                _ => {}
+            }
+        }
+    }
+
+    // Vertical, horizontal, then the corners clockwise from the top left
+    fn font(&self, highlighted: bool) -> [&'static str; 6] {
+        let [v, h] = match (self.style, highlighted, self.shape) {
+            (BorderStyle::Solid, false, _) => ["│", "─"],
+            (BorderStyle::Solid, true, Shape::Box) => ["║", "═"],
+            (BorderStyle::Solid, true, _) => ["┃", "━"],
+            (BorderStyle::Dashed, false, _) => ["┆", "┄"],
+            (BorderStyle::Dashed, true, _) => ["┇", "┅"],
+            (BorderStyle::Dotted, false, _) => ["┊", "┈"],
+            (BorderStyle::Dotted, true, _) => ["┋", "┉"],
+        };
+        let [a, b, c, d] = match (self.shape, highlighted, self.style) {
+            (Shape::Box, false, _) => ["┌", "┐", "┘", "└"],
+            (Shape::Box, true, BorderStyle::Solid) => ["╔", "╗", "╝", "╚"],
+            (Shape::Box, true, _) => ["┏", "┓", "┛", "┗"],
+            (Shape::Round | Shape::Cylinder, _, _) => ["╭", "╮", "╯", "╰"],
+        };
+        [v, h, a, b, c, d]
+    }
+
+    // Nodes mentioned again, when showing changes : light sides like nodes
+    // that aren't highlighted, but heavy top and bottom
+    fn mentioned_font(&self) -> [&'static str; 6] {
+        let [v, _, a, b, c, d] = self.font(false);
+        let h = match self.style {
+            BorderStyle::Solid => "━",
+            BorderStyle::Dashed => "┅",
+            BorderStyle::Dotted => "┉",
+        };
+        let [a, b, c, d] = match self.shape {
+            Shape::Box => ["┍", "┑", "┙", "┕"],
+            Shape::Round | Shape::Cylinder => [a, b, c, d],
+        };
+        [v, h, a, b, c, d]
+    }
+}
+
+const DIMMED_FONT: [&str; 6] = ["┊", "┈", "┌", "┐", "┘", "└"];
+
+// Split a path like "Cafe:Espresso Machine" on the colons that are not part of
+// a description or of the attributes
+fn split_path(path: &str) -> Vec<&str> {
+    let mut segments = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, char) in path.char_indices() {
+        match char {
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth -= 1,
+            ':' if depth <= 0 => {
+                segments.push(&path[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push(&path[start..]);
+    segments
+}
+
+// A problem found while reading a source document, lines and columns start at
+// 1. Problems about the whole document are on line 0.
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+// What a path segment like "DB [orders] {shape=cylinder}" says about a node
+#[derive(PartialEq, Debug, Default)]
+struct NodeSpec<'a> {
+    name: &'a str,
+    description: Option<&'a str>,
+    attributes: Vec<(&'a str, &'a str)>,
+}
+
+// "-A -> B" hides links, but "- A -> B" is an item of a list and "--> B" an
+// arrow missing its first node
+fn removed_links(instruction: &str) -> Option<&str> {
+    instruction
+        .strip_prefix('-')
+        .filter(|l| !l.starts_with(['-', '>']) && !l.starts_with(char::is_whitespace))
+}
+
+// "Cafe : Barista [makes coffee]" as "Cafe:Barista", to compare paths
+fn normalize_path(path: &str) -> String {
+    split_path(path)
+        .into_iter()
+        .map(|segment| parse_segment(segment).name)
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<&str>>()
+        .join(":")
+}
+
+// Replace an alias at the start of a path by the path it stands for, so that
+// "wand [hot]" can mean "Cafe:Espresso Machine:Steam Wand [hot]"
+fn expand_alias(aliases: &HashMap<&str, String>, path: &str) -> String {
+    let path = path.trim();
+    let name = parse_segment(split_path(path)[0]).name;
+    match aliases.get(name) {
+        Some(expanded) => format!("{}{}", expanded, &path[name.len()..]),
+        None => path.to_string(),
+    }
+}
+
+// Peel the description in square brackets and the attributes in curly braces
+// from the end of the segment, what remains is the name
+fn parse_segment(segment: &str) -> NodeSpec<'_> {
+    let mut spec = NodeSpec::default();
+    let mut rest = segment.trim();
+    loop {
+        let open = if rest.ends_with(']') {
+            '['
+        } else if rest.ends_with('}') {
+            '{'
+        } else {
+            break;
+        };
+        let Some(start) = rest.rfind(open) else {
+            break;
+        };
+        if rest[..start].trim().is_empty() {
+            break;
+        }
+        let inside = rest[start + 1..rest.len() - 1].trim();
+        if open == '[' {
+            spec.description = Some(inside);
+        } else {
+            spec.attributes.extend(parse_attributes(inside));
+        }
+        rest = rest[..start].trim();
+    }
+    spec.name = rest;
+    spec
+}
+
+// "shape=cylinder, style=dashed" into key value pairs
+fn parse_attributes(str: &str) -> Vec<(&str, &str)> {
+    str.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => (pair, ""),
+        })
+        .collect()
+}
+
+#[test]
+fn parse_segment_test() {
+    assert_eq!(
+        parse_segment(" Cafe "),
+        NodeSpec {
+            name: "Cafe",
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        parse_segment("Twilio [voice gateway]"),
+        NodeSpec {
+            name: "Twilio",
+            description: Some("voice gateway"),
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        parse_segment("[not a description]"),
+        NodeSpec {
+            name: "[not a description]",
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        parse_segment("DB [orders] {shape=cylinder, style=dashed}"),
+        NodeSpec {
+            name: "DB",
+            description: Some("orders"),
+            attributes: vec![("shape", "cylinder"), ("style", "dashed")],
+        }
+    );
+    assert_eq!(
+        parse_segment("User{shape=round} [caller]"),
+        NodeSpec {
+            name: "User",
+            description: Some("caller"),
+            attributes: vec![("shape", "round")],
+        }
+    );
+    assert_eq!(
+        split_path("Cafe:Clock [shows hh:mm]"),
+        vec!["Cafe", "Clock [shows hh:mm]"]
+    );
+}
 impl Individual {
     fn sample_node_id(&self) -> usize {
         let mut rng = thread_rng();
         self.nodes.choose(&mut rng).unwrap().id
     }
-    fn upsert_node(&mut self, name: &str, parent: Option<usize>) -> usize {
+    fn upsert_node(&mut self, spec: &NodeSpec, parent: Option<usize>) -> usize {
         if let Some(found) = self
             .nodes
             .iter()
-            .find(|n| n.name == name && (parent == n.parent))
+            .position(|n| n.name == spec.name && (parent == n.parent))
         {
-            return found.id;
+            let node = &mut self.nodes[found];
+            if let Some(description) = spec.description {
+                node.description = Some(description.to_string());
+            }
+            node.apply_attributes(&spec.attributes);
+            (self.nodes[found].position.w, self.nodes[found].position.h) =
+                self.box_size(&self.nodes[found]);
+            return found;
         }
+        self.add_node(spec, parent)
+    }
 
-        // let mut rng = thread_rng();
-        let name = format!("{}", name);
-        let w = name.chars().count() as i32 + 4;
+    // A new node, even when one with the same name is already there, for
+    // formats where nodes have ids and names are only labels
+    fn add_node(&mut self, spec: &NodeSpec, parent: Option<usize>) -> usize {
         let id = self.nodes.len();
 
         let depth = if let Some(parent_id) = parent {
@@ -982,31 +1470,48 @@ impl Individual {
         } else {
             0
         };
-        let new_node = Node {
+        let mut new_node = Node {
             id,
             parent,
             depth,
-            name,
+            name: spec.name.to_string(),
+            display_name: None,
+            description: spec.description.map(|d| d.to_string()),
+            shape: Shape::Box,
+            style: BorderStyle::Solid,
             fixed: false,
-            position: self.random_position(w),
+            position: Rectangle {
+                x: 0,
+                y: 0,
+                w: 0,
+                h: 0,
+            },
         };
+        new_node.apply_attributes(&spec.attributes);
+        let (w, h) = self.box_size(&new_node);
+        new_node.position = self.random_position(w, h);
         self.nodes.push(new_node);
-        let mut cursor = parent;
-        loop {
-            if let Some(id) = cursor {
-                cursor = self.nodes[id].parent;
-            } else {
-                break;
-            }
-        }
 
         id
     }
 
-    fn upsert_link(&mut self, from: usize, to: usize) -> usize {
-        if let Some(link) = self.links.iter().find(|link| {
+    // The link between two nodes, in either direction
+    fn find_link(&self, a: usize, b: usize) -> Option<usize> {
+        self.links
+            .iter()
+            .find(|link| (link.from, link.to) == (a, b) || (link.from, link.to) == (b, a))
+            .map(|link| link.id)
+    }
+
+    // Mentioning a link again with a plain arrow keeps its style, so that a story
+    // can highlight a dashed link without redrawing it solid
+    fn upsert_link(&mut self, from: usize, to: usize, style: LinkStyle) -> usize {
+        if let Some(link) = self.links.iter_mut().find(|link| {
             (link.from == from && link.to == to) || (link.from == to && link.to == from)
         }) {
+            if style != LinkStyle::Solid {
+                link.style = style;
+            }
             link.id
         } else {
             let id = self.links.len();
@@ -1018,90 +1523,263 @@ impl Individual {
                 end: 0,
                 fixed: false,
                 mode: random(),
+                style,
+                label: None,
             });
             id
         }
     }
 
+    fn empty(width: i32, height: i32) -> Individual {
+        Individual {
+            nodes: vec![],
+            links: vec![],
+            width,
+            height,
+            descendants_ids: None,
+            fit_to_grid: false,
+        }
+    }
+
+    // Lines that can't be understood are skipped, and charts of 10x10 or less
+    // panic. parse checks both first.
     pub fn from_string(str: &str, width: i32, height: i32) -> (Individual, Vec<StoryStep>) {
         #[cfg(feature = "console_error_panic_hook")]
         console_error_panic_hook::set_once();
 
         assert!(width > 10);
         assert!(height > 10);
-        let mut individual = Individual {
-            nodes: vec![],
-            links: vec![],
-            width,
-            height,
-            descendants_ids: None,
-        };
-        let mut story = vec![StoryStep {
-            visible_nodes_ids: HashSet::new(),
-            visible_link_ids: HashSet::new(),
-            highlighted_nodes_ids: HashSet::new(),
-            highlighted_link_ids: HashSet::new(),
-            md: String::new(),
-        }];
+        let mut individual = Individual::empty(width, height);
+        let mut story = vec![StoryStep::default()];
 
-        str.lines().for_each(|l| {
-            if l.contains("->") && !l.starts_with("//") && !l.contains("<") {
-                let current_story = story.last().unwrap();
-                if !current_story.md.is_empty() {
-                    story.push(StoryStep {
-                        visible_nodes_ids: current_story.visible_nodes_ids.clone(),
-                        visible_link_ids: current_story.visible_link_ids.clone(),
-                        highlighted_nodes_ids: HashSet::new(),
-                        highlighted_link_ids: HashSet::new(),
-                        md: String::new(),
-                    })
-                }
-                let mut previous = None;
-                for id in l.split("->") {
-                    let id = id.trim();
-                    let mut parent = None;
-                    let mut new_node_id = None;
-                    id.split(":").for_each(|path| {
-                        if path.trim() != "" {
-                            let id = individual.upsert_node(path.trim(), parent);
-                            story.last_mut().unwrap().visible_nodes_ids.insert(id);
-                            story.last_mut().unwrap().highlighted_nodes_ids.insert(id);
-                            new_node_id = Some(id);
-                            parent = Some(id);
+        let options = DocumentOptions::parse(str);
+        let mut aliases: HashMap<&str, String> = HashMap::new();
+        let mut display_names: Vec<(String, &str)> = vec![];
+        document_lines(str, &options)
+            .into_iter()
+            .for_each(|line| match line {
+                Line::Instruction(l) => {
+                    next_step(&mut story);
+                    // "-A -> B" hides the links of the line, not their nodes
+                    if let Some(l) = removed_links(l) {
+                        let (paths, _) = split_arrows(l);
+                        let ids: Vec<Option<usize>> = paths
+                            .into_iter()
+                            .map(|path| individual.find_node(&expand_alias(&aliases, path)))
+                            .collect();
+                        for pair in ids.windows(2) {
+                            if let [Some(from), Some(to)] = pair {
+                                if let Some(id) = individual.find_link(*from, *to) {
+                                    story.last_mut().unwrap().hide_link(id);
+                                }
+                            }
                         }
-                    });
-
-                    if let Some(to) = new_node_id {
-                        if let Some(from) = previous {
-                            let id = individual.upsert_link(from, to);
-                            story.last_mut().unwrap().visible_link_ids.insert(id);
-                            story.last_mut().unwrap().highlighted_link_ids.insert(id);
+                        return;
+                    }
+                    let mut previous = None;
+                    let (paths, styles) = split_arrows(l);
+                    for (index, id) in paths.into_iter().enumerate() {
+                        let path = individual.make_node(&expand_alias(&aliases, id));
+                        for id in path.iter() {
+                            story.last_mut().unwrap().visible_nodes_ids.insert(*id);
+                            story.last_mut().unwrap().highlighted_nodes_ids.insert(*id);
                         }
 
-                        previous = Some(to);
+                        if let Some(&to) = path.last() {
+                            if let Some(from) = previous {
+                                let id = individual.upsert_link(from, to, styles[index - 1]);
+                                story.last_mut().unwrap().visible_link_ids.insert(id);
+                                story.last_mut().unwrap().highlighted_link_ids.insert(id);
+                            }
+
+                            previous = Some(to);
+                        }
                     }
                 }
-            } else {
-                story.last_mut().unwrap().md.push_str(l);
-                story.last_mut().unwrap().md.push_str("\n");
+                Line::Focus(path) => {
+                    next_step(&mut story);
+                    if let Some(id) = individual.find_node(&expand_alias(&aliases, path)) {
+                        individual.focus_node(story.last_mut().unwrap(), id);
+                    }
+                }
+                Line::Highlight(l) => {
+                    next_step(&mut story);
+                    let (paths, _) = split_arrows(l);
+                    let mut previous = None;
+                    for path in paths {
+                        let leaf = individual.find_node(&expand_alias(&aliases, path));
+                        if let Some(id) = leaf {
+                            individual.highlight_path(story.last_mut().unwrap(), id);
+                        }
+                        if let (Some(from), Some(to)) = (previous, leaf) {
+                            if let Some(id) = individual.find_link(from, to) {
+                                let step = story.last_mut().unwrap();
+                                step.visible_link_ids.insert(id);
+                                step.highlighted_link_ids.insert(id);
+                            }
+                        }
+                        previous = leaf;
+                    }
+                }
+                Line::Remove(path) => {
+                    next_step(&mut story);
+                    if let Some(id) = individual.find_node(&expand_alias(&aliases, path)) {
+                        individual.hide_node(story.last_mut().unwrap(), id);
+                    }
+                }
+                Line::Alias(name, path, _) if name.is_empty() || path.is_empty() => {}
+                Line::Alias(name, path, display) => {
+                    let path = expand_alias(&aliases, path);
+                    if let Some(display) = display {
+                        display_names.push((path.clone(), display));
+                    }
+                    aliases.insert(name, path);
+                }
+                Line::Text(l) => {
+                    story.last_mut().unwrap().md.push_str(l);
+                    story.last_mut().unwrap().md.push('\n');
+                }
+                Line::Skipped => {}
+            });
+        // Display names apply to their node wherever it is declared
+        for (path, display) in display_names {
+            if let Some(id) = individual.find_node(&path) {
+                individual.nodes[id].display_name = Some(display.to_string());
+                (
+                    individual.nodes[id].position.w,
+                    individual.nodes[id].position.h,
+                ) = individual.box_size(&individual.nodes[id]);
             }
-        });
+        }
         individual.recompute_descendants_ids();
+        if options.fit_to_grid {
+            individual.set_fit_to_grid(true);
+        }
         // We don't sort nodes by depth here because their position is their id
+        let mut story = options.output.arrange(story);
+        if options.highlight_changes {
+            mark_changes(&mut story);
+        }
         (individual, story)
     }
 
-    fn make_node(&mut self, id: &str) -> Option<usize> {
+    // Like from_string, but the document is checked first, for empty node
+    // names, arrows missing a node on one side, !focus and remove: lines
+    // pointing to nothing, and charts too small to draw
+    pub fn parse(
+        str: &str,
+        width: i32,
+        height: i32,
+    ) -> Result<(Individual, Vec<StoryStep>), Vec<ParseError>> {
+        let errors = document_errors(str, width, height);
+        if errors.is_empty() {
+            Ok(Individual::from_string(str, width, height))
+        } else {
+            Err(errors)
+        }
+    }
+
+    // The leaf of a path, if all of its nodes exist
+    fn find_node(&self, path: &str) -> Option<usize> {
+        let mut parent = None;
+        for segment in split_path(path) {
+            let name = parse_segment(segment).name;
+            if !name.is_empty() {
+                parent = Some(
+                    self.nodes
+                        .iter()
+                        .position(|n| n.name == name && n.parent == parent)?,
+                );
+            }
+        }
+        parent
+    }
+
+    // Whether a node is the given one or inside it
+    fn is_within(&self, id: usize, ancestor: usize) -> bool {
+        let mut cursor = Some(id);
+        while cursor.is_some_and(|c| c != ancestor) {
+            cursor = self.nodes[cursor.unwrap()].parent;
+        }
+        cursor.is_some()
+    }
+
+    // Show and highlight a node and its ancestors, like an instruction would
+    fn highlight_path(&self, step: &mut StoryStep, id: usize) {
+        let mut cursor = Some(id);
+        while let Some(id) = cursor {
+            step.visible_nodes_ids.insert(id);
+            step.highlighted_nodes_ids.insert(id);
+            cursor = self.nodes[id].parent;
+        }
+    }
+
+    // Highlight a node, everything inside it and the links between them, and
+    // dim the rest of the chart
+    fn focus_node(&self, step: &mut StoryStep, id: usize) {
+        self.highlight_path(step, id);
+        for node in self.nodes.iter() {
+            if self.is_within(node.id, id) {
+                step.visible_nodes_ids.insert(node.id);
+                step.highlighted_nodes_ids.insert(node.id);
+            }
+        }
+        for link in self.links.iter() {
+            if self.is_within(link.from, id) && self.is_within(link.to, id) {
+                step.visible_link_ids.insert(link.id);
+                step.highlighted_link_ids.insert(link.id);
+            }
+        }
+        step.dim_others = true;
+    }
+
+    // Hide a node with everything inside it, and the links to any of them
+    fn hide_node(&self, step: &mut StoryStep, id: usize) {
+        for node in self.nodes.iter() {
+            if !self.is_within(node.id, id) {
+                continue;
+            }
+            step.visible_nodes_ids.remove(&node.id);
+            step.highlighted_nodes_ids.remove(&node.id);
+            for link in self.links.iter() {
+                if link.from == node.id || link.to == node.id {
+                    step.hide_link(link.id);
+                }
+            }
+        }
+    }
+
+    // Returns the ids of every node along the path, the last one being the leaf
+    fn make_node(&mut self, id: &str) -> Vec<usize> {
         let mut parent = None;
-        let mut new_node_id = None;
-        id.split(":").for_each(|path| {
-            if path.trim() != "" {
-                let id = self.upsert_node(path.trim(), parent);
-                new_node_id = Some(id);
+        let mut path_ids = vec![];
+        split_path(id).into_iter().for_each(|segment| {
+            let spec = parse_segment(segment);
+            if !spec.name.is_empty() {
+                let id = self.upsert_node(&spec, parent);
+                path_ids.push(id);
                 parent = Some(id);
             }
         });
-        new_node_id
+        path_ids
+    }
+
+    fn box_size(&self, node: &Node) -> (i32, i32) {
+        let (w, h) = node.label_size();
+        if self.fit_to_grid {
+            (fit_width_on_grid(w), h)
+        } else {
+            (w, h)
+        }
+    }
+
+    // Round the boxes widths and the envelopes up to whole grid cells, so that
+    // the borders of the chart line up
+    pub fn set_fit_to_grid(&mut self, fit_to_grid: bool) {
+        self.fit_to_grid = fit_to_grid;
+        for id in 0..self.nodes.len() {
+            (self.nodes[id].position.w, self.nodes[id].position.h) = self.box_size(&self.nodes[id]);
+        }
     }
 
     fn envelopes(&self) -> Vec<Rectangle> {
@@ -1111,6 +1789,9 @@ impl Individual {
             let parent_id = n.parent;
             if let Some(parent_id) = parent_id {
                 result[parent_id] = result[parent_id].gobble_up(&result[n.id]);
+                if self.fit_to_grid {
+                    result[parent_id] = result[parent_id].snapped_to_grid();
+                }
             }
         });
         result
@@ -1133,64 +1814,51 @@ impl Individual {
     }
 
     pub fn to_string(&self, step: &StoryStep) -> String {
-        let bold = ["║", "═", "╔", "╗", "╝", "╚"];
-        let normal = ["│", "─", "┌", "┐", "┘", "└"];
-
-        // Initialize empty canvas
-        let mut lines = vec![];
-        for _ in 0..self.height {
-            let mut line = vec![];
-            for _ in 0..self.width {
-                line.push(' ');
-            }
-            lines.push(line)
-        }
-
-        let mut xmin = self.width as usize;
-        let mut xmax = 0;
-        let mut ymin = self.height as usize;
-        let mut ymax = 0;
+        let mut canvas = Canvas::new(self.width, self.height);
 
         //  draw envelopes
-        let mut draw_char = |x: i32, y: i32, str: &str| {
-            let x = x as usize;
-            let y = y as usize;
-            xmin = min(xmin, x);
-            xmax = max(xmax, x);
-            ymin = min(ymin, y);
-            ymax = max(ymax, y);
-            str.chars().enumerate().for_each(|(dx, char)| {
-                if x + dx > 0 && x + dx < self.width as usize {
-                    if y > 0 && y < self.height as usize {
-                        lines[y][x + dx] = char;
-                    }
-                }
-            })
-        };
         let envelopes = self.envelopes();
 
         for node in self.nodes.iter() {
             if !step.visible_nodes_ids.contains(&node.id) {
                 continue;
             }
-            let font = if step.highlighted_nodes_ids.contains(&node.id) {
-                bold
+            let highlighted = step.highlighted_nodes_ids.contains(&node.id);
+            // When showing changes, new nodes are drawn like highlighted ones,
+            // and the ones mentioned again get their own font
+            let new = step
+                .changes
+                .as_ref()
+                .map(|c| c.new_nodes_ids.contains(&node.id));
+            let (font, lid) = if step.dim_others && !highlighted {
+                (DIMMED_FONT, ["├", "┤"])
+            } else if highlighted && new == Some(false) {
+                (node.mentioned_font(), ["┝", "┥"])
+            } else if highlighted || new == Some(true) {
+                (node.font(true), ["┣", "┫"])
             } else {
-                normal
+                (node.font(false), ["├", "┤"])
             };
             let Rectangle { x, y, w, h } = envelopes[node.id];
-            draw_char(x, y, font[2]);
-            draw_char(x + w - 1, y, font[3]);
-            draw_char(x + w - 1, y + h - 1, font[4]);
-            draw_char(x, y + h - 1, font[5]);
+            canvas.draw(x, y, font[2]);
+            canvas.draw(x + w - 1, y, font[3]);
+            canvas.draw(x + w - 1, y + h - 1, font[4]);
+            canvas.draw(x, y + h - 1, font[5]);
 
             for xi in x + 1..x + w - 1 {
-                draw_char(xi, y, font[1]);
-                draw_char(xi, y + h - 1, font[1]);
+                canvas.draw(xi, y, font[1]);
+                canvas.draw(xi, y + h - 1, font[1]);
             }
             for yi in y + 1..y + h - 1 {
-                draw_char(x, yi, font[0]);
-                draw_char(x + w - 1, yi, font[0]);
+                canvas.draw(x, yi, font[0]);
+                canvas.draw(x + w - 1, yi, font[0]);
+            }
+            if node.shape == Shape::Cylinder {
+                canvas.draw(x, y + 1, lid[0]);
+                canvas.draw(x + w - 1, y + 1, lid[1]);
+                for xi in x + 1..x + w - 1 {
+                    canvas.draw(xi, y + 1, font[1]);
+                }
             }
         }
 
@@ -1198,7 +1866,12 @@ impl Individual {
             if !step.highlighted_link_ids.contains(&link.id) {
                 continue;
             }
-            let font = bold;
+            let font = match &step.changes {
+                Some(changes) => link
+                    .style
+                    .change_font(changes.new_link_ids.contains(&link.id)),
+                None => link.style.font(),
+            };
 
             let from = &envelopes[link.from];
             let to = &envelopes[link.to];
@@ -1215,43 +1888,49 @@ impl Individual {
                     let angle_character =
                         angle_character(&last_direction, &current_direction, &font);
                     last_direction = current_direction;
-                    draw_char(last_point.x, last_point.y, angle_character);
+                    canvas.draw(last_point.x, last_point.y, angle_character);
                     last_point = last_point.moved_in_direction_of(&point);
                 }
             }
-            draw_char(
+            canvas.draw(
                 last_point.x,
                 last_point.y,
                 angle_character(&last_direction, &to_dir, &font),
             )
         }
 
+        for link in self.links.iter() {
+            if !step.highlighted_link_ids.contains(&link.id) {
+                continue;
+            }
+            if let Some(label) = &link.label {
+                let stops = stops_of_link(&envelopes[link.from], &envelopes[link.to], link).1;
+                let (position, text) = link_label_position(&stops, label);
+                canvas.draw(position.x, position.y, &text);
+            }
+        }
+
         for node in self.nodes.iter() {
             if !step.visible_nodes_ids.contains(&node.id) {
                 continue;
             }
-            draw_char(node.position.x + 2, node.position.y + 1, &node.name);
+            for (index, line) in node.label().iter().enumerate() {
+                let y = node.position.y + 1 + node.shape.header_rows() + index as i32;
+                canvas.draw(node.position.x + 2, y, line);
+            }
         }
 
-        if ymin > ymax {
-            return String::new();
-        }
-        lines[ymin..=ymax]
-            .into_iter()
-            .map(|line| format!("    {}", line[xmin..=xmax].into_iter().collect::<String>()))
-            .collect::<Vec<String>>()
-            .join("\n")
-            + "\n"
+        canvas.to_markdown()
     }
 
-    fn random_position(&self, width: i32) -> Rectangle {
+    fn random_position(&self, width: i32, height: i32) -> Rectangle {
         let mut rng = thread_rng();
-        return Rectangle {
-            x: fit_on_x_grid(rng.gen_range(1..self.width - width)),
-            y: fit_on_y_grid(rng.gen_range(1..self.height - 3)),
+        Rectangle {
+            x: fit_on_x_grid(rng.gen_range(1..max(2, self.width - width))),
+            y: fit_on_y_grid(rng.gen_range(1..max(2, self.height - height))),
             w: width,
-            h: 3,
-        };
+            h: height,
+        }
     }
     fn score_params(&self) -> RulesParams {
         let descendants_ids = self.descendants_ids.clone().unwrap();
@@ -1262,17 +1941,14 @@ impl Individual {
             .enumerate()
             .map(|(link_index, link)| {
                 let rects = stops_to_rects(
-                    stops_of_link(&envelopes[link.from], &envelopes[link.to], &link).1,
+                    stops_of_link(&envelopes[link.from], &envelopes[link.to], link).1,
                 );
                 (link_index, link.clone(), rects)
             })
             .collect();
 
         let envelope_borders: Vec<[Rectangle; 4]> = envelopes.iter().map(|e| e.borders()).collect();
-        let mut nodes_scores = vec![];
-        for _ in &self.nodes {
-            nodes_scores.push(0);
-        }
+        let nodes_scores = vec![0; self.nodes.len()];
         RulesParams {
             individual: self.clone(),
             descendants_ids,
@@ -1285,17 +1961,14 @@ impl Individual {
     pub fn score(&self) -> (i32, Vec<i32>) {
         let mut rules_params = self.score_params();
         let mut total = 0;
-        get_rules()
-            .iter()
-            .enumerate()
-            .for_each(|(index, (_, factor, rule))| {
-                if *factor > 0 {
-                    let score = rule.deref()(&mut rules_params) * factor;
-                    total += score;
-                }
-            });
+        get_rules().iter().for_each(|(_, factor, rule)| {
+            if *factor > 0 {
+                let score = rule.deref()(&mut rules_params) * factor;
+                total += score;
+            }
+        });
 
-        return (total, rules_params.nodes_scores);
+        (total, rules_params.nodes_scores)
     }
 
     pub fn improve(&mut self) {
@@ -1345,6 +2018,7 @@ impl Individual {
 
             let hottest_node_ids = scored.into_iter().map(|t| t.0).collect::<Vec<usize>>();
 
+            #[allow(clippy::never_loop)]
             let mut try_to_move = |x: i32, y: i32, id: usize, with_descendants: bool| loop {
                 let ids = vec![id];
                 let ids = if with_descendants {
@@ -1447,7 +2121,8 @@ impl Individual {
             FDG => fdg(self),
             MoveOne => {
                 let id = self.sample_node_id();
-                let pos = self.random_position(self.nodes[id].position.w);
+                let pos =
+                    self.random_position(self.nodes[id].position.w, self.nodes[id].position.h);
                 self.nodes[id].position = pos;
             }
             MoveHalf => {
@@ -1503,13 +2178,17 @@ impl Individual {
                     .unwrap()
                     .0;
 
-                let pos = self.random_position(self.nodes[id].position.w);
+                let pos =
+                    self.random_position(self.nodes[id].position.w, self.nodes[id].position.h);
                 self.nodes[id].position = pos;
             }
             // Full random
             FullRandom => {
                 for index in 0..self.nodes.len() {
-                    self.nodes[index].position = self.random_position(self.nodes[index].position.w);
+                    self.nodes[index].position = self.random_position(
+                        self.nodes[index].position.w,
+                        self.nodes[index].position.h,
+                    );
                 }
 
                 for link in self.links.iter_mut() {
@@ -1526,8 +2205,392 @@ impl Individual {
     }
 }
 
+#[test]
+fn multi_line_labels_test() {
+    let (mut individual, _) = Individual::from_string(
+        "Caller -> Twilio [voice gateway]\nTwilio -> Queue of callers waiting for an agent",
+        80,
+        40,
+    );
+    assert_eq!(individual.nodes.len(), 3);
+    assert_eq!(
+        individual.nodes[1].description,
+        Some("voice gateway".to_string())
+    );
+    assert_eq!(individual.nodes[0].position.h, 3);
+    assert_eq!(individual.nodes[1].position.h, 7);
+    assert_eq!(
+        individual.nodes[2].label(),
+        vec!["Queue of callers waiting", "for an agent"]
+    );
+
+    individual.nodes[1].position.x = 2;
+    individual.nodes[1].position.y = 2;
+    let drawn = individual.to_string(&StoryStep {
+        visible_nodes_ids: HashSet::from([1]),
+        visible_link_ids: HashSet::new(),
+        highlighted_nodes_ids: HashSet::from([1]),
+        highlighted_link_ids: HashSet::new(),
+        md: String::new(),
+        dim_others: false,
+        changes: None,
+    });
+    assert_eq!(
+        drawn,
+        [
+            "    ╔═══════════════╗",
+            "    ║ Twilio        ║",
+            "    ║ voice gateway ║",
+            "    ║               ║",
+            "    ║               ║",
+            "    ║               ║",
+            "    ╚═══════════════╝\n",
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn node_shapes_test() {
+    let (mut individual, _) = Individual::from_string(
+        "User{shape=round} -> DB{shape=cylinder} -> Queue{style=dashed}",
+        80,
+        40,
+    );
+    let render_alone = |individual: &mut Individual, id: usize, highlighted: bool| {
+        individual.nodes[id].position.x = 2;
+        individual.nodes[id].position.y = 2;
+        individual.to_string(&StoryStep {
+            visible_nodes_ids: HashSet::from([id]),
+            visible_link_ids: HashSet::new(),
+            highlighted_nodes_ids: if highlighted {
+                HashSet::from([id])
+            } else {
+                HashSet::new()
+            },
+            highlighted_link_ids: HashSet::new(),
+            md: String::new(),
+            dim_others: false,
+            changes: None,
+        })
+    };
+
+    assert_eq!(
+        render_alone(&mut individual, 0, false),
+        "    ╭──────╮\n    │ User │\n    ╰──────╯\n"
+    );
+    assert_eq!(
+        render_alone(&mut individual, 0, true),
+        "    ╭━━━━━━╮\n    ┃ User ┃\n    ╰━━━━━━╯\n"
+    );
+    assert_eq!(
+        render_alone(&mut individual, 1, false),
+        [
+            "    ╭────╮",
+            "    ├────┤",
+            "    │ DB │",
+            "    │    │",
+            "    │    │",
+            "    │    │",
+            "    ╰────╯\n",
+        ]
+        .join("\n")
+    );
+    assert_eq!(
+        render_alone(&mut individual, 2, false),
+        "    ┌┄┄┄┄┄┄┄┐\n    ┆ Queue ┆\n    └┄┄┄┄┄┄┄┘\n"
+    );
+    assert_eq!(
+        render_alone(&mut individual, 2, true),
+        "    ┏┅┅┅┅┅┅┅┓\n    ┇ Queue ┇\n    ┗┅┅┅┅┅┅┅┛\n"
+    );
+}
+
+#[test]
+fn link_styles_test() {
+    let (mut individual, story) = Individual::from_string("A --> B\nA -> B\nB ..> C", 80, 40);
+    assert_eq!(individual.links[0].style, LinkStyle::Dashed);
+    assert_eq!(individual.links[1].style, LinkStyle::Dotted);
+
+    for (id, x) in [(0, 2), (1, 20), (2, 40)] {
+        individual.nodes[id].position.x = x;
+        individual.nodes[id].position.y = 6;
+    }
+    for link in individual.links.iter_mut() {
+        link.start = 0;
+        link.end = 0;
+    }
+    // Leave B from its right side, so that the two links don't touch
+    individual.links[1].start = individual.nodes[1].position.w;
+    let drawn = individual.to_string(&story[0]);
+    assert!(drawn.contains(&format!("┌{}┐", "┄".repeat(17))));
+    assert!(drawn.contains("╌╌╌╌╌╌╌╌╌╌"));
+}
+
+#[test]
+fn fit_to_grid_test() {
+    let (mut individual, _) = Individual::from_string(include_str!("../samples/coffee.md"), 80, 40);
+    let on_grid_line = |position: i32, grid: i32| (position + 1).rem_euclid(grid) == 0;
+
+    // Before, boxes end on arbitrary columns
+    let widths: Vec<i32> = individual.nodes.iter().map(|n| n.position.w).collect();
+    assert_eq!(widths, vec![12, 8, 8, 11, 11, 11, 20, 15, 7, 16, 14]);
+
+    individual.set_fit_to_grid(true);
+    let widths: Vec<i32> = individual.nodes.iter().map(|n| n.position.w).collect();
+    assert_eq!(widths, vec![17, 11, 11, 11, 11, 11, 23, 17, 11, 17, 17]);
+
+    let envelopes = individual.envelopes();
+    for node in individual.nodes.iter() {
+        if individual.nodes.iter().any(|n| n.parent == Some(node.id)) {
+            let envelope = &envelopes[node.id];
+            assert!(on_grid_line(envelope.x, GRID_WIDTH));
+            assert!(on_grid_line(envelope.x + envelope.w - 1, GRID_WIDTH));
+            assert!(on_grid_line(envelope.y, GRID_HEIGHT));
+            assert!(on_grid_line(envelope.y + envelope.h - 1, GRID_HEIGHT));
+        }
+    }
+
+    // The document can ask for it too
+    let (individual, _) = Individual::from_string("<!-- obm:fit=grid -->\nCafe -> Barista", 80, 40);
+    assert!(individual.fit_to_grid);
+    assert_eq!(individual.nodes[0].position.w, 11);
+}
+
+#[test]
+fn wide_names_test() {
+    let (mut individual, story) = Individual::from_string("東京 -> 🍣 Sushi", 80, 40);
+    assert_eq!(individual.nodes[0].position.w, 4 + 4);
+    assert_eq!(individual.nodes[1].position.w, 8 + 4);
+
+    individual.nodes[0].position.x = 2;
+    individual.nodes[0].position.y = 2;
+    individual.nodes[1].position.x = 20;
+    individual.nodes[1].position.y = 2;
+
+    let drawn = individual.to_string(&story[0]);
+    assert!(drawn.contains("║ 東京 ║"));
+    assert!(drawn.contains("║ 🍣 Sushi ║"));
+    // Every line of the chart should take the same number of columns
+    let widths: HashSet<i32> = drawn.lines().map(display_width).collect();
+    assert_eq!(widths.len(), 1);
+}
+
+#[test]
+fn aliases_test() {
+    let (individual, story) = Individual::from_string(
+        [
+            "!alias machine = Cafe:Espresso Machine",
+            "!alias wand = machine:Steam Wand \"Wand\"",
+            "!alias barista = Cafe:Barista",
+            "barista -> wand [hot]",
+            "The barista steams the milk.",
+            "machine:Cup -> barista",
+        ]
+        .join("\n")
+        .as_str(),
+        80,
+        40,
+    );
+    let names: Vec<(&str, Option<usize>)> = individual
+        .nodes
+        .iter()
+        .map(|n| (n.name.as_str(), n.parent))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("Cafe", None),
+            ("Barista", Some(0)),
+            ("Espresso Machine", Some(0)),
+            ("Steam Wand", Some(2)),
+            ("Cup", Some(2)),
+        ]
+    );
+    assert_eq!(individual.nodes[3].description, Some("hot".to_string()));
+    assert_eq!(story[0].md, "The barista steams the milk.\n");
+    assert_eq!((individual.links[1].from, individual.links[1].to), (4, 1));
+
+    // The steam wand is drawn with its display name
+    assert_eq!(individual.nodes[3].label(), vec!["Wand", "hot"]);
+    assert_eq!(individual.nodes[3].position.w, 8);
+    assert_eq!(individual.nodes[2].display_name, None);
+
+    let errors: Vec<String> = document_errors("!alias my wand = Wand\n!alias cup =", 80, 40)
+        .iter()
+        .map(|e| e.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "line 1, column 8: expected a single word before the equal sign",
+            "line 2, column 8: nothing to alias cup to",
+        ]
+    );
+}
+
+#[test]
+fn removal_test() {
+    let (individual, story) = Individual::from_string(
+        [
+            "Caller -> Twilio:Voice -> Callee",
+            "The call goes through.",
+            "-Twilio:Voice -> Callee",
+            "The callee hangs up.",
+            "remove: Twilio",
+            "The call is over.",
+            "Caller -> Callee",
+            "They meet instead.",
+            "-Callee -> Caller",
+            "And part again.",
+        ]
+        .join("\n")
+        .as_str(),
+        80,
+        40,
+    );
+    let ids = |set: &HashSet<usize>| {
+        let mut ids: Vec<usize> = set.iter().copied().collect();
+        ids.sort();
+        ids
+    };
+    assert_eq!(story.len(), 5);
+    assert_eq!(ids(&story[0].visible_link_ids), vec![0, 1]);
+    assert_eq!(ids(&story[1].visible_nodes_ids), vec![0, 1, 2, 3]);
+    assert_eq!(ids(&story[1].visible_link_ids), vec![0]);
+    assert_eq!(ids(&story[2].visible_nodes_ids), vec![0, 3]);
+    assert!(story[2].visible_link_ids.is_empty());
+    assert_eq!(ids(&story[3].visible_link_ids), vec![2]);
+    // Links are removed whichever way they are written
+    assert!(story[4].visible_link_ids.is_empty());
+    // Hidden nodes keep their place in the layout
+    assert_eq!(individual.nodes.len(), 4);
+    // List items aren't removals
+    assert_eq!(removed_links("-Callee -> Caller"), Some("Callee -> Caller"));
+    assert_eq!(removed_links("- Caller -> Callee"), None);
+}
+
+#[test]
+fn focus_test() {
+    let (mut individual, story) = Individual::from_string(
+        [
+            "Customer -> Cafe:Machine:Wand",
+            "Cafe:Machine:Wand -> Cafe:Machine:Cup",
+            "The milk is steamed.",
+            "!focus Cafe:Machine",
+            "Inside the machine.",
+            "!highlight Customer -> Cafe:Machine:Wand",
+            "Back to the customer.",
+            "!highlight Cafe:Machine:Cup -> Cafe:Machine:Wand",
+            "The cup, written the other way around.",
+        ]
+        .join("\n")
+        .as_str(),
+        80,
+        40,
+    );
+    let ids = |set: &HashSet<usize>| {
+        let mut ids: Vec<usize> = set.iter().copied().collect();
+        ids.sort();
+        ids
+    };
+    assert_eq!(ids(&story[1].highlighted_nodes_ids), vec![1, 2, 3, 4]);
+    assert_eq!(ids(&story[1].highlighted_link_ids), vec![1]);
+    assert!(story[1].dim_others);
+    assert_eq!(ids(&story[2].highlighted_nodes_ids), vec![0, 1, 2, 3]);
+    assert_eq!(ids(&story[2].highlighted_link_ids), vec![0]);
+    assert!(!story[2].dim_others);
+    assert_eq!(ids(&story[3].highlighted_link_ids), vec![1]);
+
+    individual.nodes[0].position = Rectangle {
+        x: 2,
+        y: 2,
+        w: 12,
+        h: 3,
+    };
+    let drawn = individual.to_string(&StoryStep {
+        visible_nodes_ids: HashSet::from([0]),
+        visible_link_ids: HashSet::new(),
+        highlighted_nodes_ids: HashSet::new(),
+        highlighted_link_ids: HashSet::new(),
+        dim_others: true,
+        changes: None,
+        md: String::new(),
+    });
+    assert_eq!(
+        drawn,
+        ["    ┌┈┈┈┈┈┈┈┈┈┈┐", "    ┊ Customer ┊", "    └┈┈┈┈┈┈┈┈┈┈┘\n",].join("\n")
+    );
+}
+
+#[test]
+fn changes_test() {
+    let (mut individual, story) = Individual::from_string(
+        [
+            "<!-- obm:highlight=changes -->",
+            "A -> B",
+            "First.",
+            "B --> C",
+            "Second.",
+            "!highlight A -> B",
+            "Third.",
+        ]
+        .join("\n")
+        .as_str(),
+        80,
+        40,
+    );
+    let changes = |index: usize| {
+        let changes = story[index].changes.clone().unwrap();
+        let mut nodes: Vec<usize> = changes.new_nodes_ids.into_iter().collect();
+        nodes.sort();
+        let mut links: Vec<usize> = changes.new_link_ids.into_iter().collect();
+        links.sort();
+        (nodes, links)
+    };
+    assert_eq!(changes(0), (vec![], vec![]));
+    assert_eq!(changes(1), (vec![0, 1], vec![0]));
+    assert_eq!(changes(2), (vec![2], vec![1]));
+    assert_eq!(changes(3), (vec![], vec![]));
+
+    for (id, x) in [(0, 2), (1, 12), (2, 22)] {
+        individual.nodes[id].position = Rectangle {
+            x,
+            y: 2,
+            w: 5,
+            h: 3,
+        };
+    }
+    for link in individual.links.iter_mut() {
+        (link.start, link.end, link.mode) = (10, 10, true);
+    }
+    // New nodes are drawn like highlighted ones, nodes mentioned again with
+    // heavy top and bottom, and the others lightly. New links are heavier than
+    // the ones shown before, and keep their dashes.
+    assert_eq!(
+        individual.to_string(&story[2]),
+        [
+            "    ┌───┐     ┍━━━┑     ╔═══╗",
+            "    │ A │     │ B │     ║ C ║",
+            "    └───┘     ┕━━━┙     ╚═══╝",
+            "                ┗┅┅┅┅┅┅┅┅┅┛  \n",
+        ]
+        .join("\n")
+    );
+    assert_eq!(
+        individual.to_string(&story[3]),
+        [
+            "    ┍━━━┑     ┍━━━┑     ┌───┐",
+            "    │ A │     │ B │     │ C │",
+            "    ┕━━━┙     ┕━━━┙     └───┘",
+            "      └─────────┘            \n",
+        ]
+        .join("\n")
+    );
+}
+
 fn stops_to_rects(points: Vec<Point>) -> Vec<Rectangle> {
-    if points.len() == 0 {
+    if points.is_empty() {
         return vec![];
     }
     if points.len() == 1 {
@@ -1545,13 +2608,13 @@ fn stops_to_rects(points: Vec<Point>) -> Vec<Rectangle> {
 
     for pt in iter {
         // if pt != last_point {
-        let corner_1 = if rectangles.len() > 0 {
-            last_point.moved_in_direction_of(&pt)
+        let corner_1 = if !rectangles.is_empty() {
+            last_point.moved_in_direction_of(pt)
         } else {
             last_point.clone()
         };
 
-        rectangles.push(Rectangle::from_points(&corner_1, &pt));
+        rectangles.push(Rectangle::from_points(&corner_1, pt));
         last_point = pt;
         // }
     }
@@ -1594,8 +2657,286 @@ pub struct Link {
     end: i32,
     fixed: bool,
     mode: bool,
+    style: LinkStyle,
+    label: Option<String>,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkStyle {
+    #[default]
+    Solid,
+    Dashed,
+    Thick,
+    Dotted,
+}
+
+// Longest arrows first, so that "-->" isn't read as "-" followed by "->"
+const ARROWS: [(&str, LinkStyle); 4] = [
+    ("-->", LinkStyle::Dashed),
+    ("==>", LinkStyle::Thick),
+    ("..>", LinkStyle::Dotted),
+    ("->", LinkStyle::Solid),
+];
+
+impl LinkStyle {
+    // Same layout as the nodes fonts, see angle_character
+    fn font(&self) -> [&'static str; 6] {
+        match self {
+            LinkStyle::Solid => ["║", "═", "╔", "╗", "╝", "╚"],
+            LinkStyle::Dashed => ["┆", "┄", "┌", "┐", "┘", "└"],
+            LinkStyle::Thick => ["┃", "━", "┏", "┓", "┛", "┗"],
+            LinkStyle::Dotted => ["╎", "╌", "┌", "┐", "┘", "└"],
+        }
+    }
+
+    // When showing changes, new links are drawn heavier than the links shown
+    // before, with the same dashes
+    fn change_font(&self, new: bool) -> [&'static str; 6] {
+        match (self, new) {
+            (LinkStyle::Solid, false) => ["│", "─", "┌", "┐", "┘", "└"],
+            (LinkStyle::Thick, false) => ["│", "━", "┍", "┑", "┙", "┕"],
+            (LinkStyle::Dashed, true) => ["┇", "┅", "┏", "┓", "┛", "┗"],
+            (LinkStyle::Dotted, true) => ["╏", "╍", "┏", "┓", "┛", "┗"],
+            _ => self.font(),
+        }
+    }
+}
+
+fn has_arrow(line: &str) -> bool {
+    ARROWS.iter().any(|(arrow, _)| line.contains(arrow))
+}
+
+// Cut "A --> B -> C" into the paths ["A ", " B ", " C"] and the styles of the
+// arrows between them
+fn split_arrows(line: &str) -> (Vec<&str>, Vec<LinkStyle>) {
+    let mut paths = vec![];
+    let mut styles = vec![];
+    let mut start = 0;
+    let mut index = 0;
+    while index < line.len() {
+        if let Some((arrow, style)) = ARROWS
+            .iter()
+            .find(|(arrow, _)| line[index..].starts_with(arrow))
+        {
+            paths.push(&line[start..index]);
+            styles.push(*style);
+            index += arrow.len();
+            start = index;
+        } else {
+            index += line[index..].chars().next().unwrap().len_utf8();
+        }
+    }
+    paths.push(&line[start..]);
+    (paths, styles)
 }
 
+// Column of a part of a line, in characters from 1. The part must be a slice
+// of the line.
+fn column_of(line: &str, part: &str) -> usize {
+    let offset = part.as_ptr() as usize - line.as_ptr() as usize;
+    line[..offset].chars().count() + 1
+}
+
+// Charts need room for a few boxes
+fn size_error(width: i32, height: i32) -> Option<ParseError> {
+    if width <= 10 || height <= 10 {
+        Some(ParseError {
+            line: 0,
+            column: 0,
+            message: format!(
+                "the chart should be larger than 10x10, not {}x{}",
+                width, height
+            ),
+        })
+    } else {
+        None
+    }
+}
+
+fn document_errors(document: &str, width: i32, height: i32) -> Vec<ParseError> {
+    let mut errors: Vec<ParseError> = size_error(width, height).into_iter().collect();
+
+    // The nodes declared so far, like "Cafe" and "Cafe:Barista", which !focus
+    // and remove: lines can point to
+    let mut declared: HashSet<String> = HashSet::new();
+    let mut aliases: HashMap<&str, String> = HashMap::new();
+    let options = DocumentOptions::parse(document);
+    // document_lines gives one line for each line of the document
+    let lines = document.lines().zip(document_lines(document, &options));
+    for (index, (source, line)) in lines.enumerate() {
+        let error = |part: &str, message: String| ParseError {
+            line: index + 1,
+            column: column_of(source, part),
+            message,
+        };
+        let declares = matches!(line, Line::Instruction(l) if removed_links(l).is_none());
+        let paths = match line {
+            Line::Instruction(l) | Line::Highlight(l) => {
+                let l = removed_links(l).unwrap_or(l);
+                let (paths, _) = split_arrows(l);
+                for (position, path) in paths.iter().enumerate() {
+                    let message = if position == 0 {
+                        "nothing before the arrow"
+                    } else if position == paths.len() - 1 {
+                        "nothing after the arrow"
+                    } else {
+                        "nothing between two arrows"
+                    };
+                    if path.trim().is_empty() {
+                        errors.push(error(path, message.to_string()));
+                    } else if declares {
+                        // Declaring Cafe:Barista declares Cafe too
+                        let names = normalize_path(&expand_alias(&aliases, path));
+                        let names: Vec<&str> = names.split(':').collect();
+                        for depth in 1..=names.len() {
+                            declared.insert(names[..depth].join(":"));
+                        }
+                    }
+                }
+                paths
+            }
+            Line::Alias(name, path, _) => {
+                let is_word = name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+                if name.is_empty() || !is_word {
+                    errors.push(error(
+                        name,
+                        "expected a single word before the equal sign".to_string(),
+                    ));
+                } else if path.is_empty() {
+                    errors.push(error(name, format!("nothing to alias {} to", name)));
+                } else {
+                    let path = expand_alias(&aliases, path);
+                    aliases.insert(name, path);
+                }
+                vec![path]
+            }
+            Line::Focus(path) | Line::Remove(path) => {
+                // from_string ignores what doesn't exist yet
+                let target = normalize_path(&expand_alias(&aliases, path));
+                if !target.is_empty() && !declared.contains(&target) {
+                    errors.push(error(
+                        path,
+                        format!("{} isn't declared before this line", target),
+                    ));
+                }
+                vec![path]
+            }
+            _ => vec![],
+        };
+        for path in paths.into_iter().filter(|p| !p.trim().is_empty()) {
+            for segment in split_path(path) {
+                let spec = parse_segment(segment);
+                if spec.name.is_empty() {
+                    errors.push(error(
+                        segment,
+                        format!("empty node name in {}", path.trim()),
+                    ));
+                }
+                // Unknown values would silently draw the default shape or style
+                for (key, value) in spec.attributes {
+                    let known = match key {
+                        "shape" => Shape::from_name(value).is_some(),
+                        "style" => BorderStyle::from_name(value).is_some(),
+                        _ => true,
+                    };
+                    if !known {
+                        errors.push(error(
+                            segment,
+                            format!("unknown {} {} for {}", key, value, spec.name),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    errors
+}
+
+#[test]
+fn parse_test() {
+    assert!(Individual::parse("A -> B\n-A -> B\n!focus A\nremove: B", 80, 40).is_ok());
+    assert_eq!(
+        Individual::parse("Some text\nA -> -> B", 80, 40)
+            .err()
+            .unwrap(),
+        vec![ParseError {
+            line: 2,
+            column: 5,
+            message: "nothing between two arrows".to_string()
+        }]
+    );
+    let errors = Individual::parse("  Cafe::Barista -> B ->\n-> Café\n!focus Cafe:", 8, 40)
+        .err()
+        .unwrap()
+        .into_iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(
+        errors,
+        [
+            "line 0, column 0: the chart should be larger than 10x10, not 8x40",
+            "line 1, column 24: nothing after the arrow",
+            "line 1, column 8: empty node name in Cafe::Barista",
+            "line 2, column 1: nothing before the arrow",
+            "line 3, column 13: empty node name in Cafe:",
+        ]
+    );
+    assert_eq!(
+        Individual::parse("A -> DB{shape=cilinder} -> Q{style=wavy}", 80, 40)
+            .err()
+            .unwrap()
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<String>>(),
+        [
+            "line 1, column 5: unknown shape cilinder for DB",
+            "line 1, column 27: unknown style wavy for Q",
+        ]
+    );
+    assert_eq!(
+        Individual::parse(
+            "G ->\n!alias b = Cafe:Barista\n!focus b\nCafe -> b\nremove: Cafe:b",
+            80,
+            40
+        )
+        .err()
+        .unwrap()
+        .into_iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<String>>(),
+        [
+            "line 1, column 5: nothing after the arrow",
+            "line 3, column 8: Cafe:Barista isn't declared before this line",
+            "line 5, column 9: Cafe:b isn't declared before this line",
+        ]
+    );
+}
+
+#[test]
+fn split_arrows_test() {
+    assert_eq!(
+        split_arrows("A -> B"),
+        (vec!["A ", " B"], vec![LinkStyle::Solid])
+    );
+    assert_eq!(
+        split_arrows("A --> B ==> C ..> D"),
+        (
+            vec!["A ", " B ", " C ", " D"],
+            vec![LinkStyle::Dashed, LinkStyle::Thick, LinkStyle::Dotted]
+        )
+    );
+    assert_eq!(
+        split_arrows("Café-->東京"),
+        (vec!["Café", "東京"], vec![LinkStyle::Dashed])
+    );
+    assert!(has_arrow("A ..> B"));
+    assert!(!has_arrow("A - B"));
+}
+
+#[allow(clippy::needless_range_loop)]
 fn fdg(source: &mut Individual) {
     let _starting_score = source.score().0;
     let center = (source.width as f32 / 2.0, source.height as f32 / 2.0);
@@ -1688,6 +3029,7 @@ fn fdg(source: &mut Individual) {
         source.nodes[id].position.y = nodes[id].1 as i32;
     }
 }
+#[allow(dead_code)]
 const MUTATIONS_TOTAL: i32 = 7;
 
 #[derive(PartialEq, Debug, Clone, Copy, Eq, Hash)]
@@ -1704,36 +3046,308 @@ pub enum Mutation {
     Transpose,
 }
 
-#[wasm_bindgen]
-pub fn md_to_md(source: String, width: i32, height: i32) -> String {
-    let (mut best_world, story) = Individual::from_string(&source, width, height);
+// The best layout found so far, and how many rounds of mutations in a row
+// failed to improve it. The search is done after 20 of them.
+pub struct LayoutSearch {
+    pub best_world: Individual,
+    pub best_score: i32,
+    runs_with_no_improvement: usize,
+}
 
-    // Mostly for the first run
-    best_world.improve();
+impl LayoutSearch {
+    pub fn new(mut world: Individual) -> LayoutSearch {
+        // Mostly for the first run
+        world.improve();
+        let best_score = world.score().0;
+        LayoutSearch {
+            best_world: world,
+            best_score,
+            runs_with_no_improvement: 0,
+        }
+    }
+
+    pub fn done(&self) -> bool {
+        self.runs_with_no_improvement >= 20
+    }
+
+    // Keep the world if it scores better than the best one
+    pub fn offer(&mut self, world: Individual) -> bool {
+        let score = world.score().0;
+        if score >= self.best_score {
+            return false;
+        }
+        self.best_world = world;
+        self.best_score = score;
+        self.runs_with_no_improvement = 0;
+        true
+    }
 
-    let max_stalled_runs = 20;
+    // Mutations of the best world, tried together
+    pub fn round(&mut self, candidates: impl IntoIterator<Item = Individual>) {
+        let mut improved = false;
+        for candidate in candidates {
+            improved |= self.offer(candidate);
+        }
+        if !improved {
+            self.runs_with_no_improvement += 1;
+        }
+    }
+}
 
-    let mut best_score = best_world.score().0;
-    let mut runs_with_no_improvement = 0;
-    while runs_with_no_improvement < max_stalled_runs {
-        let mut clone = best_world.clone();
+// Improve the layout of a chart, stopping after 20 mutations in a row fail to
+// improve it
+fn optimize(world: Individual) -> Individual {
+    let mut search = LayoutSearch::new(world);
+    while !search.done() {
+        let mut clone = search.best_world.clone();
         clone.mutate();
-        let score = clone.score().0;
-        if score < best_score {
-            best_score = score;
-            runs_with_no_improvement = 0;
-            best_world = clone;
-        } else {
-            runs_with_no_improvement += 1
+        search.round([clone]);
+    }
+    search.best_world
+}
+
+// Milliseconds since some point in time. std::time isn't available in the
+// browser, so WASM asks javascript instead.
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> f64 {
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_namespace = Date)]
+        fn now() -> f64;
+    }
+    now()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
+// The same search as optimize, one time slice at a time, so that a page can
+// show every improvement and stop whenever it wants
+#[wasm_bindgen]
+pub struct LayoutSession {
+    search: LayoutSearch,
+    story: Vec<StoryStep>,
+}
+
+#[wasm_bindgen(getter_with_clone)]
+pub struct LayoutProgress {
+    pub md: String,
+    pub score: i32,
+    // 20 mutations in a row didn't improve anything, like optimize
+    pub done: bool,
+}
+
+#[wasm_bindgen]
+impl LayoutSession {
+    // Throws the errors of the source, md_errors has them with their lines
+    #[wasm_bindgen(constructor)]
+    pub fn new(source: String, width: i32, height: i32) -> Result<LayoutSession, String> {
+        let (world, story) = parse_source(&source, width, height)?;
+        Ok(LayoutSession {
+            search: LayoutSearch::new(world),
+            story,
+        })
+    }
+
+    // Mutate for about ms milliseconds, and return the best layout so far
+    pub fn step(&mut self, ms: f64) -> LayoutProgress {
+        let start = now_ms();
+        while !self.search.done() && now_ms() - start < ms {
+            let mut clone = self.search.best_world.clone();
+            clone.mutate();
+            self.search.round([clone]);
         }
+        LayoutProgress {
+            md: story_to_md(&self.search.best_world, &self.story),
+            score: self.search.best_score,
+            done: self.search.done(),
+        }
+    }
+
+    // The best layout so far, as json, to share it with the sessions searching
+    // the same source in other workers
+    pub fn best_layout(&self) -> String {
+        self.search.best_world.to_json(&[])
+    }
+
+    // Continue from a layout shared by another session if it scores better.
+    // Layouts that don't fit this source are ignored.
+    pub fn offer(&mut self, layout: String) -> bool {
+        let Ok((world, _)) = Individual::from_json(&layout) else {
+            return false;
+        };
+        let best_world = &self.search.best_world;
+        let same_graph = world.nodes.len() == best_world.nodes.len()
+            && world.links.len() == best_world.links.len()
+            && world.width == best_world.width
+            && world.height == best_world.height;
+        same_graph && self.search.offer(world)
+    }
+}
+
+#[test]
+fn layout_session_test() {
+    let mut session = LayoutSession::new("A -> B\nB -> C\nC -> A".to_string(), 80, 40).unwrap();
+    let first = session.step(0.0);
+    assert!(!first.done);
+    assert!(first.md.contains("A"));
+
+    let mut progress = session.step(10.0);
+    while !progress.done {
+        assert!(progress.score <= first.score);
+        progress = session.step(10.0);
     }
+    assert_eq!(progress.score, session.search.best_world.score().0);
 
+    // Another session only takes the layout if it is better than its own
+    let mut other = LayoutSession::new("A -> B\nB -> C\nC -> A".to_string(), 80, 40).unwrap();
+    let other_score = other.step(0.0).score;
+    assert_eq!(
+        other.offer(session.best_layout()),
+        progress.score < other_score
+    );
+    assert!(other.step(0.0).score <= progress.score);
+    let mut unrelated = LayoutSession::new("A -> B".to_string(), 80, 40).unwrap();
+    assert!(!unrelated.offer(session.best_layout()));
+    assert!(!unrelated.offer("{}".to_string()));
+    assert!(LayoutSession::new("A ->".to_string(), 80, 40).is_err());
+}
+
+// Parse a document for the WASM functions, which throw its errors, one per line
+fn parse_source(
+    source: &str,
+    width: i32,
+    height: i32,
+) -> Result<(Individual, Vec<StoryStep>), String> {
+    Individual::parse(source, width, height).map_err(|errors| {
+        errors
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<String>>()
+            .join("\n")
+    })
+}
+
+fn story_to_md(world: &Individual, story: &[StoryStep]) -> String {
     let mut out = String::new();
 
     for step in story {
-        out.push_str(&best_world.to_string(&step));
+        out.push_str(&world.to_string(step));
         out.push_str(&step.md);
     }
 
     out
 }
+
+#[wasm_bindgen]
+pub fn md_to_md(source: String, width: i32, height: i32) -> Result<String, String> {
+    let (world, story) = parse_source(&source, width, height)?;
+    Ok(story_to_md(&optimize(world), &story))
+}
+
+// The problems found in a document, as a json list of {line, column, message}
+#[wasm_bindgen]
+pub fn md_errors(source: String, width: i32, height: i32) -> String {
+    serde_json::to_string(&document_errors(&source, width, height)).unwrap()
+}
+
+// The warnings of lint, as a json list of {line, message}
+#[wasm_bindgen]
+pub fn md_lint(source: String, max_nodes: usize) -> String {
+    serde_json::to_string(&lint(&source, max_nodes)).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn md_to_json(source: String, width: i32, height: i32) -> Result<String, String> {
+    let (world, story) = parse_source(&source, width, height)?;
+    Ok(optimize(world).to_json(&story))
+}
+
+// A reveal.js deck, one slide per step with its text as presenter notes
+#[wasm_bindgen]
+pub fn md_to_slides(source: String, width: i32, height: i32) -> Result<String, String> {
+    let (world, story) = parse_source(&source, width, height)?;
+    Ok(optimize(world).to_slides(&story))
+}
+
+#[wasm_bindgen]
+pub fn md_to_html_slides(source: String, width: i32, height: i32) -> Result<String, String> {
+    let (world, story) = parse_source(&source, width, height)?;
+    Ok(optimize(world).to_html_slides(&story))
+}
+
+// An asciinema recording of the story, each step shown for seconds_per_step
+#[wasm_bindgen]
+pub fn md_to_asciicast(
+    source: String,
+    width: i32,
+    height: i32,
+    seconds_per_step: f32,
+) -> Result<String, String> {
+    let (world, story) = parse_source(&source, width, height)?;
+    Ok(optimize(world).to_asciicast(&story, seconds_per_step))
+}
+
+#[wasm_bindgen]
+pub fn md_to_animated_svg(
+    source: String,
+    width: i32,
+    height: i32,
+    seconds_per_step: f32,
+) -> Result<String, String> {
+    let (world, story) = parse_source(&source, width, height)?;
+    Ok(optimize(world).to_animated_svg(&story, seconds_per_step))
+}
+
+// Render a layout saved with md_to_json, without optimizing it again
+#[wasm_bindgen]
+pub fn json_to_md(layout: String) -> Result<String, String> {
+    let (world, story) = Individual::from_json(&layout).map_err(|e| e.to_string())?;
+    Ok(story_to_md(&world, &story))
+}
+
+// Lay out a Mermaid flowchart, and render it as markdown
+#[wasm_bindgen]
+pub fn mermaid_to_md(source: String, width: i32, height: i32) -> Result<String, String> {
+    let (world, story) =
+        Individual::from_mermaid(&source, width, height).map_err(|e| e.to_string())?;
+    Ok(story_to_md(&optimize(world), &story))
+}
+
+// Turn a markdown document into Mermaid charts, one per step. There is no
+// layout to compute, Mermaid does its own.
+#[wasm_bindgen]
+pub fn md_to_mermaid(source: String) -> Result<String, String> {
+    let (world, story) = parse_source(&source, 80, 40)?;
+    Ok(world.to_mermaid(&story))
+}
+
+// Export the graph of a markdown document for Graphviz, with the positions
+// found by obm if asked to
+#[wasm_bindgen]
+pub fn md_to_dot(
+    source: String,
+    width: i32,
+    height: i32,
+    positions: bool,
+) -> Result<String, String> {
+    let (world, _) = parse_source(&source, width, height)?;
+    if positions {
+        Ok(optimize(world).to_dot(true))
+    } else {
+        Ok(world.to_dot(false))
+    }
+}
+
+// Lay out a Graphviz graph, and render it as markdown
+#[wasm_bindgen]
+pub fn dot_to_md(source: String, width: i32, height: i32) -> Result<String, String> {
+    let (world, story) = Individual::from_dot(&source, width, height).map_err(|e| e.to_string())?;
+    Ok(story_to_md(&optimize(world), &story))
+}
@@ -0,0 +1,225 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::document::document_lines;
+use crate::{
+    document_errors, expand_alias, normalize_path, removed_links, split_arrows, DocumentOptions,
+    Individual, Line,
+};
+
+// Something in a document that renders, but probably not like its author
+// meant. Lines start at 1, warnings about the whole document are on line 0.
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct Warning {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+// Highlight and removal lines that mention nodes or links not declared by an
+// instruction before them, which from_string ignores silently. Also links
+// declared in both directions, which from_string merges. document_errors
+// already reports !focus and remove: lines pointing to nothing.
+fn reference_warnings(document: &str) -> Vec<Warning> {
+    let mut warnings = vec![];
+    let mut nodes: HashSet<String> = HashSet::new();
+    let mut links: HashSet<(String, String)> = HashSet::new();
+    let mut aliases: HashMap<&str, String> = HashMap::new();
+    let options = DocumentOptions::parse(document);
+    for (index, line) in document_lines(document, &options).into_iter().enumerate() {
+        let mut warn = |message: String| {
+            warnings.push(Warning {
+                line: index + 1,
+                message,
+            })
+        };
+        let (references, declared) = match line {
+            Line::Instruction(l) => match removed_links(l) {
+                Some(l) => (split_arrows(l).0, false),
+                None => (split_arrows(l).0, true),
+            },
+            Line::Highlight(l) => (split_arrows(l).0, false),
+            Line::Alias(name, path, _) => {
+                let path = expand_alias(&aliases, path);
+                aliases.insert(name, path);
+                continue;
+            }
+            _ => continue,
+        };
+        let paths: Vec<String> = references
+            .into_iter()
+            .filter(|path| !path.trim().is_empty())
+            .map(|path| normalize_path(&expand_alias(&aliases, path)))
+            .collect();
+
+        if declared {
+            for path in paths.iter() {
+                // Declaring Cafe:Barista declares Cafe too
+                let names: Vec<&str> = path.split(':').collect();
+                for depth in 1..=names.len() {
+                    nodes.insert(names[..depth].join(":"));
+                }
+            }
+            for pair in paths.windows(2) {
+                let link = (pair[0].clone(), pair[1].clone());
+                let back = (pair[1].clone(), pair[0].clone());
+                if link != back && links.contains(&back) && !links.contains(&link) {
+                    warn(format!(
+                        "{} and {} are linked in both directions, the chart shows a single link",
+                        back.1, back.0
+                    ));
+                }
+                links.insert(link);
+            }
+            continue;
+        }
+        for path in paths.iter().filter(|path| !nodes.contains(*path)) {
+            warn(format!("{} isn't declared before this line", path));
+        }
+        for pair in paths.windows(2) {
+            let link = (pair[0].clone(), pair[1].clone());
+            let back = (pair[1].clone(), pair[0].clone());
+            let linked = links.contains(&link) || links.contains(&back);
+            if nodes.contains(&link.0) && nodes.contains(&link.1) && !linked {
+                warn(format!("there is no link from {} to {}", link.0, link.1));
+            }
+        }
+    }
+    warnings
+}
+
+impl Individual {
+    // The ancestors names and the node name, like "Cafe:Barista"
+    fn path_name(&self, id: usize) -> String {
+        let node = &self.nodes[id];
+        match node.parent {
+            Some(parent) => format!("{}:{}", self.path_name(parent), node.name),
+            None => node.name.clone(),
+        }
+    }
+}
+
+// Everything suspicious in a document : instructions that don't parse, nodes
+// named almost the same way, leaves with the same name in several parents,
+// links in both directions, steps without any text, charts with more than
+// max_nodes nodes, and references to nodes or links that don't exist yet
+pub fn lint(document: &str, max_nodes: usize) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = document_errors(document, 80, 40)
+        .into_iter()
+        .map(|error| Warning {
+            line: error.line,
+            message: error.message,
+        })
+        .collect();
+    let mut warn = |message: String| warnings.push(Warning { line: 0, message });
+
+    let (world, story) = Individual::from_string(document, 80, 40);
+    if world.nodes.len() > max_nodes {
+        warn(format!(
+            "the chart has {} nodes, more than {} is hard to lay out and read",
+            world.nodes.len(),
+            max_nodes
+        ));
+    }
+
+    // Barista and barista, or "Espresso  Machine" and "Espresso Machine"
+    let mut similar: HashMap<(Option<usize>, String), usize> = HashMap::new();
+    for node in world.nodes.iter() {
+        let key = (
+            node.parent,
+            node.name
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" ")
+                .to_lowercase(),
+        );
+        match similar.get(&key) {
+            Some(&other) => warn(format!(
+                "{} and {} only differ by case or spaces",
+                world.path_name(other),
+                world.path_name(node.id)
+            )),
+            None => {
+                similar.insert(key, node.id);
+            }
+        }
+    }
+
+    let is_leaf = |id: usize| !world.nodes.iter().any(|n| n.parent == Some(id));
+    let mut leaves: HashMap<&str, usize> = HashMap::new();
+    for node in world.nodes.iter().filter(|n| is_leaf(n.id)) {
+        match leaves.get(node.name.as_str()) {
+            Some(&other) if world.nodes[other].parent != node.parent => warn(format!(
+                "{} and {} are different nodes with the same name",
+                world.path_name(other),
+                world.path_name(node.id)
+            )),
+            Some(_) => {}
+            None => {
+                leaves.insert(&node.name, node.id);
+            }
+        }
+    }
+
+    for (index, step) in story.iter().enumerate() {
+        if !step.highlighted_nodes_ids.is_empty() && step.md.trim().is_empty() {
+            let mut names: Vec<String> = step
+                .highlighted_nodes_ids
+                .iter()
+                .map(|id| world.path_name(*id))
+                .collect();
+            names.sort();
+            warn(format!(
+                "step {} ({}) has no text",
+                index + 1,
+                names.join(", ")
+            ));
+        }
+    }
+
+    warnings.extend(reference_warnings(document));
+    warnings.sort_by_key(|warning| warning.line);
+    warnings
+}
+
+#[test]
+fn lint_test() {
+    let document = [
+        "# Cafe",
+        "Cafe:Barista -> Cafe:Counter",
+        "The barista waits.",
+        "Customer -> Cafe:barista",
+        "Customer:Counter -> Customer",
+        "Customer -> Cafe:Barista",
+        "!focus Kitchen",
+        "-Cafe:Counter -> Customer",
+        "Then.",
+        "Cafe:Barista -> Customer",
+    ]
+    .join("\n");
+    let warnings: Vec<String> = lint(&document, 5).iter().map(|w| w.to_string()).collect();
+    assert_eq!(
+        warnings,
+        [
+            "the chart has 6 nodes, more than 5 is hard to lay out and read",
+            "Cafe:Barista and Cafe:barista only differ by case or spaces",
+            "Cafe:Counter and Customer:Counter are different nodes with the same name",
+            "step 4 (Cafe, Cafe:Barista, Customer) has no text",
+            "line 7: Kitchen isn't declared before this line",
+            "line 8: there is no link from Cafe:Counter to Customer",
+            "line 10: Cafe:Barista and Customer are linked in both directions, the chart shows a single link",
+        ]
+    );
+    assert!(lint("A -> B\nSome text.", 30).is_empty());
+}
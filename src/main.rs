@@ -1,14 +1,58 @@
+use std::env;
 use std::io::{self};
+use std::process;
 use std::thread;
 use std::thread::{available_parallelism, JoinHandle};
-use std::time::Instant;
-
-use crossterm::ExecutableCommand;
+use std::time::Duration;
 
 use obm::*;
 
+#[cfg(unix)]
+mod edit_tui;
+#[cfg(unix)]
+mod play;
+
+// The value following a flag, like "json" in "--format json"
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|index| args.get(index + 1))
+        .map(|value| value.as_str())
+}
+
+// A number of seconds greater than 0, small enough to wait for
+fn parse_seconds(value: &str) -> Option<Duration> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| *seconds > 0.0)
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+}
+
+// Mutate one clone of the world per thread, keep the best one, and stop after
+// 20 rounds without any improvement
+fn optimize(world: Individual) -> Individual {
+    let threads_counts = available_parallelism().unwrap().get();
+
+    let mut search = LayoutSearch::new(world);
+    while !search.done() {
+        let handles: Vec<JoinHandle<Individual>> = (0..threads_counts)
+            .map(|_index| {
+                let mut clone = search.best_world.clone();
+                thread::spawn(|| {
+                    clone.mutate();
+                    clone
+                })
+            })
+            .collect();
+        search.round(handles.into_iter().map(|handle| handle.join().unwrap()));
+    }
+    search.best_world
+}
+
 fn main() {
     let (width, height) = (80, 40);
+    let args: Vec<String> = env::args().skip(1).collect();
 
     let source: String = io::stdin()
         .lines()
@@ -16,48 +60,195 @@ fn main() {
         .collect::<Vec<String>>()
         .join("\n");
 
-    let threads_counts = available_parallelism().unwrap().get();
+    // obm lint reports suspicious constructs in a markdown document, and
+    // fails if there are any
+    if args.first().is_some_and(|a| a == "lint") {
+        let max_nodes = flag_value(&args, "--max-nodes")
+            .and_then(|count| count.parse().ok())
+            .unwrap_or(30);
+        let warnings = lint(&source, max_nodes);
+        for warning in warnings.iter() {
+            println!("{}", warning);
+        }
+        process::exit(if warnings.is_empty() { 0 } else { 1 })
+    }
 
-    let (mut best_world, story) = Individual::from_string(&source, width as i32, height as i32);
+    // Without --input and --format, documents are read and written as markdown
+    let inputs = ["json", "dot", "mermaid"];
+    let formats = ["json", "mermaid", "slides", "html", "cast", "svg", "dot"];
+    for (flag, values) in [("--input", &inputs[..]), ("--format", &formats[..])] {
+        if let Some(value) = flag_value(&args, flag).filter(|value| !values.contains(value)) {
+            eprintln!(
+                "Unknown {} {}, it should be one of {}, or left out for markdown",
+                flag,
+                value,
+                values.join(", ")
+            );
+            process::exit(1)
+        }
+    }
 
-    // Mostly for the first run
-    best_world.improve();
+    // Each step has its own chart, which only the text output can show
+    let expand_current = args.iter().any(|a| a == "--expand-current");
+    if expand_current {
+        let other_output = flag_value(&args, "--format").is_some()
+            || args.first().is_some_and(|a| a == "play" || a == "edit");
+        if other_output || flag_value(&args, "--simplify").is_none() {
+            eprintln!("--expand-current needs --simplify and only works with the text output");
+            process::exit(1)
+        }
+    }
 
-    // let mut best_score = best_world.fitness(&factors);
-    let mut previous_best_world = best_world.clone();
-    let max_stalled_runs = 20;
+    // obm play --auto 5 goes to the next step after 5 seconds
+    let auto_advance = flag_value(&args, "--auto").map(|seconds| {
+        parse_seconds(seconds).unwrap_or_else(|| {
+            eprintln!(
+                "--auto needs a number of seconds greater than 0, not {}",
+                seconds
+            );
+            process::exit(1)
+        })
+    });
 
-    let mut best_score = best_world.score().0;
-    let mut runs_with_no_improvement = 0;
-    while runs_with_no_improvement < max_stalled_runs {
-        let _start_all = Instant::now();
-        runs_with_no_improvement += 1;
-        let handles: Vec<JoinHandle<Individual>> = (0..threads_counts)
-            .into_iter()
-            .map(|_index| {
-                let mut clone = best_world.clone();
-                thread::spawn(|| {
-                    clone.mutate();
-                    clone
+    let (best_world, story) = match flag_value(&args, "--input") {
+        // A layout saved with --format json is rendered as is
+        Some("json") => Individual::from_json(&source).unwrap_or_else(|e| {
+            eprintln!("Invalid layout: {}", e);
+            process::exit(1)
+        }),
+        input => {
+            let (mut world, story) = match input {
+                Some("dot") => Individual::from_dot(&source, width, height).unwrap_or_else(|e| {
+                    eprintln!("Invalid graph: {}", e);
+                    process::exit(1)
+                }),
+                Some("mermaid") => {
+                    Individual::from_mermaid(&source, width, height).unwrap_or_else(|e| {
+                        eprintln!("Invalid flowchart: {}", e);
+                        process::exit(1)
+                    })
+                }
+                _ => Individual::parse(&source, width, height).unwrap_or_else(|errors| {
+                    for error in errors {
+                        eprintln!("Invalid document: {}", error);
+                    }
+                    process::exit(1)
+                }),
+            };
+            if args.iter().any(|a| a == "--fit-to-grid") {
+                world.set_fit_to_grid(true);
+            }
+            let max_nodes = flag_value(&args, "--simplify").map(|count| {
+                count.parse().unwrap_or_else(|_| {
+                    eprintln!("--simplify needs a number of nodes, not {}", count);
+                    process::exit(1)
                 })
-            })
-            .collect();
-
-        for handle in handles {
-            if let clone = handle.join().unwrap() {
-                let score = clone.score().0;
-                if score < best_score {
-                    best_score = score;
-                    runs_with_no_improvement = 0;
-                    previous_best_world = best_world.clone();
-                    best_world = clone;
+            });
+            if let Some(max_nodes) = max_nodes.filter(|max| !world.can_simplify_to(*max)) {
+                eprintln!(
+                    "Warning: the chart has more than {} top level nodes, they are all drawn",
+                    max_nodes
+                );
+            }
+            match max_nodes {
+                // Each step gets its own chart, with what it talks about expanded
+                Some(max_nodes) if expand_current => {
+                    let mut story = story;
+                    if args.iter().any(|a| a == "--changes") {
+                        mark_changes(&mut story);
+                    }
+                    // Steps that expand the same subtrees share a layout
+                    let mut layouts: Vec<(Individual, Individual)> = vec![];
+                    for index in 0..story.len() {
+                        let (world, story) = world.simplify_for_step(&story, index, max_nodes);
+                        let best_world = match layouts.iter().find(|(simple, _)| *simple == world) {
+                            Some((_, best_world)) => best_world.clone(),
+                            None => {
+                                let best_world = optimize(world.clone());
+                                layouts.push((world, best_world.clone()));
+                                best_world
+                            }
+                        };
+                        println!("{}", best_world.to_string(&story[index]));
+                        println!("{}", story[index].md);
+                    }
+                    return;
+                }
+                Some(max_nodes) => {
+                    let (world, story) = world.simplify(&story, max_nodes);
+                    (optimize(world), story)
                 }
+                None => (optimize(world), story),
+            }
+        }
+    };
+
+    let mut story = story;
+    if args.iter().any(|a| a == "--changes") {
+        mark_changes(&mut story);
+    }
+
+    // obm play shows the story step by step, full screen
+    if args.first().is_some_and(|a| a == "play") {
+        #[cfg(unix)]
+        play::play(&best_world, &story, auto_advance).unwrap_or_else(|e| {
+            eprintln!("Could not play the story: {}", e);
+            process::exit(1)
+        });
+        #[cfg(not(unix))]
+        {
+            let _ = auto_advance;
+            eprintln!("obm play needs a unix terminal");
+            process::exit(1)
+        }
+        return;
+    }
+
+    // obm edit moves nodes and links by hand, and saves the layout as json
+    if args.first().is_some_and(|a| a == "edit") {
+        let path = flag_value(&args, "--save").unwrap_or("layout.json");
+        #[cfg(unix)]
+        edit_tui::edit(best_world, &story, path).unwrap_or_else(|e| {
+            eprintln!("Could not edit the layout: {}", e);
+            process::exit(1)
+        });
+        #[cfg(not(unix))]
+        {
+            let _ = path;
+            eprintln!("obm edit needs a unix terminal");
+            process::exit(1)
+        }
+        return;
+    }
+
+    let seconds_per_step = flag_value(&args, "--step-seconds")
+        .and_then(|seconds| seconds.parse::<f32>().ok())
+        .unwrap_or(3.0);
+    match flag_value(&args, "--format") {
+        Some("json") => println!("{}", best_world.to_json(&story)),
+        Some("mermaid") => print!("{}", best_world.to_mermaid(&story)),
+        Some("slides") => print!("{}", best_world.to_slides(&story)),
+        Some("html") => print!("{}", best_world.to_html_slides(&story)),
+        Some("cast") => print!("{}", best_world.to_asciicast(&story, seconds_per_step)),
+        Some("svg") => print!("{}", best_world.to_animated_svg(&story, seconds_per_step)),
+        Some("dot") => print!(
+            "{}",
+            best_world.to_dot(args.iter().any(|a| a == "--positions"))
+        ),
+        _ => {
+            for step in story {
+                println!("{}", best_world.to_string(&step));
+                println!("{}", step.md);
             }
         }
     }
+}
 
-    for step in story {
-        println!("{}", best_world.to_string(&step));
-        println!("{}", step.md);
+#[test]
+fn parse_seconds_test() {
+    assert_eq!(parse_seconds("5"), Some(Duration::from_secs(5)));
+    assert_eq!(parse_seconds("0.5"), Some(Duration::from_millis(500)));
+    for value in ["0", "-1", "NaN", "inf", "1e300", "five"] {
+        assert_eq!(parse_seconds(value), None);
     }
 }
@@ -0,0 +1,742 @@
+use crate::{size_error, Individual, LinkStyle, NodeSpec, ParseError, Shape, StoryStep};
+
+// A subgraph, drawn as a box around its nodes
+struct Subgraph {
+    id: String,
+    title: String,
+    parent: Option<usize>,
+}
+
+struct MermaidNode {
+    id: String,
+    text: Option<String>,
+    shape: Option<Shape>,
+    subgraph: Option<usize>,
+}
+
+struct MermaidEdge {
+    // Its number for linkStyle, invisible links are counted too
+    index: usize,
+    from: String,
+    to: String,
+    style: LinkStyle,
+    label: Option<String>,
+}
+
+#[derive(Default)]
+struct Parser {
+    subgraphs: Vec<Subgraph>,
+    // The subgraphs that are not closed by "end" yet
+    open: Vec<usize>,
+    nodes: Vec<MermaidNode>,
+    edges: Vec<MermaidEdge>,
+    link_count: usize,
+}
+
+// The characters of one line, with the column of the cursor for errors
+struct Cursor {
+    chars: Vec<char>,
+    index: usize,
+    line: usize,
+}
+
+impl Cursor {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        prefix
+            .chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.index + i) == Some(&c))
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.index += 1;
+        }
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.index + 1,
+            message: message.to_string(),
+        }
+    }
+
+    // Read until one of the closing strings, returning the text and the
+    // closing string found
+    fn until(&mut self, closings: &[&'static str]) -> Result<(String, &'static str), ParseError> {
+        let start = self.index;
+        let mut text = String::new();
+        if self.peek() == Some('"') {
+            self.index += 1;
+            while self.peek().is_some_and(|c| c != '"') {
+                text.push(self.chars[self.index]);
+                self.index += 1;
+            }
+            if self.peek().is_none() {
+                self.index = start;
+                return Err(self.error("unclosed string"));
+            }
+            self.index += 1;
+            self.skip_spaces();
+        }
+        while self.index < self.chars.len() {
+            if let Some(closing) = closings.iter().find(|c| self.starts_with(c)) {
+                self.index += closing.chars().count();
+                return Ok((text.trim().to_string(), closing));
+            }
+            text.push(self.chars[self.index]);
+            self.index += 1;
+        }
+        self.index = start;
+        Err(self.error(&format!("expected {}", closings.join(" or "))))
+    }
+
+    fn id(&mut self) -> Result<String, ParseError> {
+        let start = self.index;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            self.index += 1;
+        }
+        if start == self.index {
+            return Err(self.error("expected a node id"));
+        }
+        Ok(self.chars[start..self.index].iter().collect())
+    }
+}
+
+// The opening and closing brackets of node shapes, longest openings first
+const SHAPES: [(&str, &str, Shape); 11] = [
+    ("[(", ")]", Shape::Cylinder),
+    ("([", "])", Shape::Round),
+    ("((", "))", Shape::Round),
+    ("[[", "]]", Shape::Box),
+    ("{{", "}}", Shape::Box),
+    ("[/", "/]", Shape::Box),
+    ("[\\", "\\]", Shape::Box),
+    ("[", "]", Shape::Box),
+    ("(", ")", Shape::Round),
+    ("{", "}", Shape::Box),
+    (">", "]", Shape::Box),
+];
+
+impl Parser {
+    fn line(&mut self, cursor: &mut Cursor) -> Result<(), ParseError> {
+        cursor.skip_spaces();
+        let rest: String = cursor.chars[cursor.index..].iter().collect();
+        let keyword = rest.split_whitespace().next().unwrap_or("");
+        match keyword {
+            "" => return Ok(()),
+            _ if rest.starts_with("%%") => return Ok(()),
+            "end" => {
+                if self.open.pop().is_none() {
+                    return Err(cursor.error("end without a subgraph"));
+                }
+                return Ok(());
+            }
+            "subgraph" => {
+                cursor.index += keyword.len();
+                cursor.skip_spaces();
+                return self.subgraph(cursor);
+            }
+            // Dashed links are dotted links with a dash array, as written by
+            // step_to_mermaid
+            "linkStyle" => {
+                let mut words = rest.split_whitespace().skip(1);
+                let indexes = words.next().unwrap_or("");
+                if words.any(|w| w.starts_with("stroke-dasharray")) {
+                    for index in indexes.split(',').filter_map(|i| i.parse::<usize>().ok()) {
+                        for edge in self.edges.iter_mut().filter(|e| e.index == index) {
+                            if edge.style == LinkStyle::Dotted {
+                                edge.style = LinkStyle::Dashed;
+                            }
+                        }
+                    }
+                }
+                return Ok(());
+            }
+            // Styling and interactions have no text equivalent
+            "direction" | "classDef" | "class" | "style" | "click" => return Ok(()),
+            _ => {}
+        }
+
+        while cursor.peek().is_some() {
+            self.statement(cursor)?;
+            cursor.skip_spaces();
+            while cursor.peek() == Some(';') {
+                cursor.index += 1;
+                cursor.skip_spaces();
+            }
+        }
+        Ok(())
+    }
+
+    // subgraph id, subgraph id [Title] or subgraph Some title
+    fn subgraph(&mut self, cursor: &mut Cursor) -> Result<(), ParseError> {
+        let rest: String = cursor.chars[cursor.index..].iter().collect();
+        let (id, title) = match rest.find('[') {
+            Some(bracket) => {
+                let id = rest[..bracket].trim().to_string();
+                cursor.index += rest[..bracket].chars().count() + 1;
+                let (title, _) = cursor.until(&["]"])?;
+                (id, title)
+            }
+            None => {
+                let title = rest.trim().trim_matches('"').to_string();
+                (title.clone(), title)
+            }
+        };
+        if id.is_empty() {
+            return Err(cursor.error("expected a subgraph name"));
+        }
+        self.subgraphs.push(Subgraph {
+            id,
+            title,
+            parent: self.open.last().copied(),
+        });
+        self.open.push(self.subgraphs.len() - 1);
+        Ok(())
+    }
+
+    // A chain like A --> B & C -->|label| D
+    fn statement(&mut self, cursor: &mut Cursor) -> Result<(), ParseError> {
+        let mut group = self.node_group(cursor)?;
+        loop {
+            cursor.skip_spaces();
+            if cursor.peek().is_none() || cursor.peek() == Some(';') {
+                return Ok(());
+            }
+            let (style, mut label) = self.link(cursor)?;
+            cursor.skip_spaces();
+            if cursor.peek() == Some('|') {
+                cursor.index += 1;
+                label = Some(cursor.until(&["|"])?.0);
+                cursor.skip_spaces();
+            }
+            let next = self.node_group(cursor)?;
+            for from in group.iter() {
+                for to in next.iter() {
+                    if let Some(style) = style {
+                        self.edges.push(MermaidEdge {
+                            index: self.link_count,
+                            from: from.clone(),
+                            to: to.clone(),
+                            style,
+                            label: label.clone().filter(|l| !l.is_empty()),
+                        });
+                    }
+                    self.link_count += 1;
+                }
+            }
+            group = next;
+        }
+    }
+
+    fn node_group(&mut self, cursor: &mut Cursor) -> Result<Vec<String>, ParseError> {
+        let mut ids = vec![self.node(cursor)?];
+        cursor.skip_spaces();
+        while cursor.peek() == Some('&') {
+            cursor.index += 1;
+            cursor.skip_spaces();
+            ids.push(self.node(cursor)?);
+            cursor.skip_spaces();
+        }
+        Ok(ids)
+    }
+
+    fn node(&mut self, cursor: &mut Cursor) -> Result<String, ParseError> {
+        let id = cursor.id()?;
+        let mut text = None;
+        let mut shape = None;
+        if let Some((opening, closing, found)) = SHAPES
+            .iter()
+            .find(|(opening, _, _)| cursor.starts_with(opening))
+        {
+            cursor.index += opening.chars().count();
+            text = Some(cursor.until(&[closing])?.0);
+            shape = Some(*found);
+        }
+        // Classes like A:::warning only change the colors
+        if cursor.starts_with(":::") {
+            cursor.index += 3;
+            cursor.id()?;
+        }
+
+        let subgraph = self.open.last().copied();
+        match self.nodes.iter_mut().find(|n| n.id == id) {
+            Some(node) => {
+                if text.is_some() {
+                    (node.text, node.shape) = (text, shape);
+                }
+                if subgraph.is_some() && node.subgraph.is_none() {
+                    node.subgraph = subgraph;
+                }
+            }
+            None => self.nodes.push(MermaidNode {
+                id: id.clone(),
+                text,
+                shape,
+                subgraph,
+            }),
+        }
+        Ok(id)
+    }
+
+    // Links look like -->, ---, -.->, ==>, with an optional text in the middle
+    // like -- text -->. Returns no style for invisible links (~~~).
+    fn link(
+        &mut self,
+        cursor: &mut Cursor,
+    ) -> Result<(Option<LinkStyle>, Option<String>), ParseError> {
+        let start = cursor.index;
+        if cursor.peek() == Some('<') {
+            cursor.index += 1;
+        }
+        let mut label = None;
+        if cursor.starts_with("~~~") {
+            while cursor.peek() == Some('~') {
+                cursor.index += 1;
+            }
+            return Ok((None, None));
+        }
+        for (opening, closings) in [
+            ("-.", &["->", "-"][..]),
+            ("--", &["-->", "---", "--o", "--x"][..]),
+            ("==", &["==>", "===", "==o", "==x"][..]),
+        ] {
+            if !cursor.starts_with(opening) {
+                continue;
+            }
+            cursor.index += opening.chars().count();
+            // The arrow continues, or a text is written before its end
+            let arrow_chars = |c: char| matches!(c, '-' | '.' | '=' | '>');
+            if cursor.peek().is_some_and(arrow_chars) {
+                while cursor.peek().is_some_and(arrow_chars) {
+                    cursor.index += 1;
+                }
+            } else if cursor.peek().is_some_and(|c| c.is_whitespace()) {
+                let (text, _) = cursor.until(closings)?;
+                label = Some(text);
+                while cursor.peek().is_some_and(arrow_chars) {
+                    cursor.index += 1;
+                }
+            }
+            // Circle and cross arrow heads, as in A --o B
+            if matches!(cursor.peek(), Some('o' | 'x'))
+                && cursor
+                    .chars
+                    .get(cursor.index + 1)
+                    .is_some_and(|c| c.is_whitespace())
+            {
+                cursor.index += 1;
+            }
+            let arrow: String = cursor.chars[start..cursor.index].iter().collect();
+            let style = if arrow.contains('.') {
+                LinkStyle::Dotted
+            } else if arrow.contains('=') {
+                LinkStyle::Thick
+            } else {
+                LinkStyle::Solid
+            };
+            return Ok((Some(style), label));
+        }
+        cursor.index = start;
+        Err(cursor.error("expected a link like -->"))
+    }
+}
+
+impl Individual {
+    // Read a Mermaid flowchart. Subgraphs become parents of their nodes, and
+    // the whole chart is shown in a single story step. Each Mermaid id is a
+    // node of its own, even when its text is the same as another one.
+    pub fn from_mermaid(
+        source: &str,
+        width: i32,
+        height: i32,
+    ) -> Result<(Individual, Vec<StoryStep>), ParseError> {
+        let mut parser = Parser::default();
+        let mut header = false;
+        for (index, line) in source.lines().enumerate() {
+            let mut cursor = Cursor {
+                chars: line.chars().collect(),
+                index: 0,
+                line: index + 1,
+            };
+            let trimmed = line.trim();
+            if header || trimmed.is_empty() || trimmed.starts_with("%%") {
+                parser.line(&mut cursor)?;
+                continue;
+            }
+            match trimmed
+                .split(|c: char| c.is_whitespace() || c == ';')
+                .next()
+            {
+                Some("flowchart" | "graph") => {
+                    header = true;
+                    // Statements can follow the header, like "graph TD; A-->B"
+                    if let Some(semicolon) = cursor.chars.iter().position(|c| *c == ';') {
+                        cursor.index = semicolon;
+                        while cursor.peek().is_some_and(|c| c == ';' || c.is_whitespace()) {
+                            cursor.index += 1;
+                        }
+                        parser.line(&mut cursor)?;
+                    }
+                }
+                _ => {
+                    cursor.skip_spaces();
+                    return Err(cursor.error("expected flowchart or graph"));
+                }
+            }
+        }
+        if !header {
+            return Err(ParseError {
+                line: 1,
+                column: 1,
+                message: "expected flowchart or graph".to_string(),
+            });
+        }
+        if !parser.open.is_empty() {
+            return Err(ParseError {
+                line: source.lines().count().max(1),
+                column: 1,
+                message: "subgraph without an end".to_string(),
+            });
+        }
+
+        if let Some(error) = size_error(width, height) {
+            return Err(error);
+        }
+
+        let mut individual = Individual::empty(width, height);
+        let mut step = StoryStep::default();
+
+        // Subgraphs are declared before the subgraphs they contain
+        let mut subgraph_ids: Vec<usize> = vec![];
+        for subgraph in parser.subgraphs.iter() {
+            let spec = NodeSpec {
+                name: &subgraph.title,
+                ..Default::default()
+            };
+            let parent = subgraph.parent.map(|p| subgraph_ids[p]);
+            subgraph_ids.push(individual.add_node(&spec, parent));
+        }
+
+        // Edges can point to a subgraph by its id
+        let mut node_ids: Vec<usize> = vec![];
+        for node in parser.nodes.iter() {
+            if let Some(subgraph) = parser.subgraphs.iter().position(|s| s.id == node.id) {
+                node_ids.push(subgraph_ids[subgraph]);
+                continue;
+            }
+            let shape = match node.shape {
+                Some(Shape::Round) => vec![("shape", "round")],
+                Some(Shape::Cylinder) => vec![("shape", "cylinder")],
+                _ => vec![],
+            };
+            // Descriptions are exported on a second line
+            let text = node
+                .text
+                .as_deref()
+                .filter(|t| !t.is_empty())
+                .unwrap_or(&node.id)
+                .replace("#quot;", "\"");
+            let (name, description) = match text.split_once("<br/>") {
+                Some((name, description)) => (name, Some(description)),
+                None => (text.as_str(), None),
+            };
+            let spec = NodeSpec {
+                name,
+                description,
+                attributes: shape,
+            };
+            let parent = node.subgraph.map(|s| subgraph_ids[s]);
+            node_ids.push(individual.add_node(&spec, parent));
+        }
+
+        let id_of = |id: &str| node_ids[parser.nodes.iter().position(|n| n.id == id).unwrap()];
+        for edge in parser.edges.iter() {
+            let id = individual.upsert_link(id_of(&edge.from), id_of(&edge.to), edge.style);
+            if edge.label.is_some() {
+                individual.links[id].label = edge.label.clone();
+            }
+            step.visible_link_ids.insert(id);
+            step.highlighted_link_ids.insert(id);
+        }
+
+        for node in individual.nodes.iter() {
+            step.visible_nodes_ids.insert(node.id);
+            step.highlighted_nodes_ids.insert(node.id);
+        }
+        individual.recompute_descendants_ids();
+        Ok((individual, vec![step]))
+    }
+
+    // The flowchart of one step. Nodes with visible children become subgraphs,
+    // and only the highlighted links are drawn, like in the text rendering.
+    pub fn step_to_mermaid(&self, step: &StoryStep) -> String {
+        let mut out = String::from("flowchart TD\n");
+        for node in self.nodes.iter().filter(|n| n.parent.is_none()) {
+            self.node_to_mermaid(node.id, step, 1, &mut out);
+        }
+
+        // Mermaid only has dotted links, dashed ones get a dash array
+        let mut dashed: Vec<String> = vec![];
+        let links = self
+            .links
+            .iter()
+            .filter(|link| step.highlighted_link_ids.contains(&link.id));
+        for (index, link) in links.enumerate() {
+            let arrow = match link.style {
+                LinkStyle::Solid => "-->",
+                LinkStyle::Dashed | LinkStyle::Dotted => "-.->",
+                LinkStyle::Thick => "==>",
+            };
+            if link.style == LinkStyle::Dashed {
+                dashed.push(index.to_string());
+            }
+            let label = match &link.label {
+                Some(label) => format!("|{}|", quoted(label)),
+                None => String::new(),
+            };
+            out.push_str(&format!(
+                "    n{} {}{} n{}\n",
+                link.from, arrow, label, link.to
+            ));
+        }
+        if !dashed.is_empty() {
+            out.push_str(&format!(
+                "    linkStyle {} stroke-dasharray:6 3\n",
+                dashed.join(",")
+            ));
+        }
+
+        let mut highlighted: Vec<String> = self
+            .nodes
+            .iter()
+            .filter(|n| {
+                step.visible_nodes_ids.contains(&n.id) && step.highlighted_nodes_ids.contains(&n.id)
+            })
+            .map(|n| format!("n{}", n.id))
+            .collect();
+        if !highlighted.is_empty() {
+            highlighted.sort();
+            out.push_str("    classDef highlighted stroke-width:3px\n");
+            out.push_str(&format!(
+                "    class {} highlighted\n",
+                highlighted.join(",")
+            ));
+        }
+        out
+    }
+
+    fn node_to_mermaid(&self, id: usize, step: &StoryStep, depth: usize, out: &mut String) {
+        if !step.visible_nodes_ids.contains(&id) {
+            return;
+        }
+        let node = &self.nodes[id];
+        let indent = "    ".repeat(depth);
+        let mut text = node.shown_name().to_string();
+        if let Some(description) = &node.description {
+            text = format!("{}<br/>{}", text, description);
+        }
+
+        let children: Vec<usize> = self
+            .nodes
+            .iter()
+            .filter(|n| n.parent == Some(id) && step.visible_nodes_ids.contains(&n.id))
+            .map(|n| n.id)
+            .collect();
+        if children.is_empty() {
+            let (opening, closing) = match node.shape {
+                Shape::Box => ("[", "]"),
+                Shape::Round => ("(", ")"),
+                Shape::Cylinder => ("[(", ")]"),
+            };
+            out.push_str(&format!(
+                "{}n{}{}{}{}\n",
+                indent,
+                id,
+                opening,
+                quoted(&text),
+                closing
+            ));
+        } else {
+            out.push_str(&format!("{}subgraph n{} [{}]\n", indent, id, quoted(&text)));
+            for child in children {
+                self.node_to_mermaid(child, step, depth + 1, out);
+            }
+            out.push_str(&format!("{}end\n", indent));
+        }
+    }
+
+    // A markdown document with one Mermaid chart per step, followed by its text
+    pub fn to_mermaid(&self, story: &[StoryStep]) -> String {
+        let mut out = String::new();
+        for step in story {
+            // Like the text rendering, prose before the first chart stays alone
+            if !step.visible_nodes_ids.is_empty() {
+                out.push_str("```mermaid\n");
+                out.push_str(&self.step_to_mermaid(step));
+                out.push_str("```\n");
+            }
+            out.push_str(&step.md);
+        }
+        out
+    }
+}
+
+// Mermaid texts are quoted so that brackets in names don't end them
+fn quoted(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "#quot;"))
+}
+
+#[test]
+fn from_mermaid_test() {
+    let (individual, story) = Individual::from_mermaid(
+        "
+%% The order
+flowchart LR
+    Customer([Customer]) -->|orders| cashier[Cashier]
+    subgraph cafe [Cafe]
+        cashier
+        subgraph machine [Espresso Machine]
+            wand[Steam Wand] & cup
+        end
+        fridge[(Refrigerator)]
+    end
+    cashier -.-> Barista -- brews --> cup; fridge ==> wand
+    Barista --> machine
+    classDef hot fill:#f96
+",
+        80,
+        40,
+    )
+    .unwrap();
+
+    let names: Vec<(&str, Option<usize>)> = individual
+        .nodes
+        .iter()
+        .map(|n| (n.name.as_str(), n.parent))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("Cafe", None),
+            ("Espresso Machine", Some(0)),
+            ("Customer", None),
+            ("Cashier", Some(0)),
+            ("Steam Wand", Some(1)),
+            ("cup", Some(1)),
+            ("Refrigerator", Some(0)),
+            ("Barista", None),
+        ]
+    );
+    assert_eq!(individual.nodes[2].shape, Shape::Round);
+    assert_eq!(individual.nodes[6].shape, Shape::Cylinder);
+
+    let links: Vec<(usize, usize, LinkStyle, Option<&str>)> = individual
+        .links
+        .iter()
+        .map(|l| (l.from, l.to, l.style, l.label.as_deref()))
+        .collect();
+    assert_eq!(
+        links,
+        vec![
+            (2, 3, LinkStyle::Solid, Some("orders")),
+            (3, 7, LinkStyle::Dotted, None),
+            (7, 5, LinkStyle::Solid, Some("brews")),
+            (6, 4, LinkStyle::Thick, None),
+            (7, 1, LinkStyle::Solid, None),
+        ]
+    );
+    assert_eq!(story[0].visible_nodes_ids.len(), 8);
+
+    // Statements can follow the header on the same line
+    let (individual, _) = Individual::from_mermaid("graph TD; A-->B; B-->C", 80, 40).unwrap();
+    let names: Vec<&str> = individual.nodes.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, vec!["A", "B", "C"]);
+    assert_eq!(individual.links.len(), 2);
+}
+
+#[test]
+fn from_mermaid_error_test() {
+    assert_eq!(
+        Individual::from_mermaid("flowchart TD\n  A --> B\n  B -->", 80, 40).err(),
+        Some(ParseError {
+            line: 3,
+            column: 8,
+            message: "expected a node id".to_string()
+        })
+    );
+    assert_eq!(
+        Individual::from_mermaid("sequenceDiagram\n  A->>B: hi", 80, 40)
+            .err()
+            .map(|e| e.line),
+        Some(1)
+    );
+    assert!(Individual::from_mermaid("graph TD\nsubgraph a\nb", 80, 40).is_err());
+    assert_eq!(
+        Individual::from_mermaid("graph TD\nA --> B", 80, 8)
+            .err()
+            .unwrap()
+            .message,
+        "the chart should be larger than 10x10, not 80x8"
+    );
+}
+
+#[test]
+fn from_mermaid_ids_test() {
+    // Nodes are told apart by their id, not their text
+    let (individual, _) =
+        Individual::from_mermaid("graph TD\n  a[Server] --> b[Server]\n  b --> a", 80, 40).unwrap();
+    let names: Vec<&str> = individual.nodes.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, vec!["Server", "Server"]);
+    assert_eq!(individual.links.len(), 1);
+    assert_eq!((individual.links[0].from, individual.links[0].to), (0, 1));
+}
+
+#[test]
+fn to_mermaid_test() {
+    let (individual, story) =
+        Individual::from_string("Cafe:Barista -> Customer [thirsty]\nserves", 80, 40);
+    assert_eq!(
+        individual.to_mermaid(&story),
+        [
+            "```mermaid",
+            "flowchart TD",
+            "    subgraph n0 [\"Cafe\"]",
+            "        n1[\"Barista\"]",
+            "    end",
+            "    n2[\"Customer<br/>thirsty\"]",
+            "    n1 --> n2",
+            "    classDef highlighted stroke-width:3px",
+            "    class n0,n1,n2 highlighted",
+            "```",
+            "serves",
+            "",
+        ]
+        .join("\n")
+    );
+
+    // The chart of a step can be read back
+    let (imported, _) =
+        Individual::from_mermaid(&individual.step_to_mermaid(&story[0]), 80, 40).unwrap();
+    let names: Vec<&str> = imported.nodes.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, vec!["Cafe", "Barista", "Customer"]);
+    assert_eq!(imported.nodes[2].description, Some("thirsty".to_string()));
+    assert_eq!((imported.links[0].from, imported.links[0].to), (1, 2));
+
+    // Dashed and dotted links stay apart
+    let (individual, story) = Individual::from_string("A --> B ..> C --> D", 80, 40);
+    let mermaid = individual.step_to_mermaid(&story[0]);
+    assert!(mermaid.contains("    linkStyle 0,2 stroke-dasharray:6 3\n"));
+    let (imported, _) = Individual::from_mermaid(&mermaid, 80, 40).unwrap();
+    let styles: Vec<LinkStyle> = imported.links.iter().map(|l| l.style).collect();
+    assert_eq!(
+        styles,
+        vec![LinkStyle::Dashed, LinkStyle::Dotted, LinkStyle::Dashed]
+    );
+}
@@ -0,0 +1,160 @@
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+
+use obm::{Individual, StoryStep};
+
+#[derive(PartialEq, Debug)]
+enum Command {
+    Show(usize),
+    Quit,
+    Nothing,
+}
+
+// What a key does while the step `current` out of `count` is shown. Digits are
+// collected in `typed`, and enter jumps to the step with that number.
+fn command(key: KeyEvent, current: usize, count: usize, typed: &mut String) -> Command {
+    let last = count - 1;
+    match key.code {
+        KeyCode::Char(digit) if digit.is_ascii_digit() => {
+            typed.push(digit);
+            Command::Nothing
+        }
+        KeyCode::Enter if !typed.is_empty() => {
+            let number: usize = typed.parse().unwrap_or(1);
+            typed.clear();
+            Command::Show(number.clamp(1, count) - 1)
+        }
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Command::Quit,
+        KeyCode::Char('q') | KeyCode::Esc => Command::Quit,
+        KeyCode::Right | KeyCode::Down | KeyCode::Enter | KeyCode::Char(' ' | 'n' | 'j') => {
+            Command::Show((current + 1).min(last))
+        }
+        KeyCode::Left | KeyCode::Up | KeyCode::Backspace | KeyCode::Char('p' | 'k') => {
+            Command::Show(current.saturating_sub(1))
+        }
+        KeyCode::Home | KeyCode::Char('g') => Command::Show(0),
+        KeyCode::End | KeyCode::Char('G') => Command::Show(last),
+        _ => Command::Nothing,
+    }
+}
+
+fn render(
+    out: &mut impl Write,
+    world: &Individual,
+    story: &[StoryStep],
+    current: usize,
+    typed: &str,
+) -> io::Result<()> {
+    queue!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    let step = &story[current];
+    // Raw mode doesn't go back to the start of the line by itself
+    for line in world.to_string(step).lines().chain(step.md.lines()) {
+        write!(out, "{}\r\n", line)?;
+    }
+    write!(
+        out,
+        "\r\n[{}/{}] ←/→ move, number and enter to jump{}, q to quit",
+        current + 1,
+        story.len(),
+        if typed.is_empty() {
+            String::new()
+        } else {
+            format!(" (step {})", typed)
+        }
+    )?;
+    out.flush()
+}
+
+// Show the story one step at a time, full screen. With auto_advance, the next
+// step comes after that delay unless a key is pressed.
+pub fn play(
+    world: &Individual,
+    story: &[StoryStep],
+    auto_advance: Option<Duration>,
+) -> io::Result<()> {
+    if story.is_empty() {
+        return Ok(());
+    }
+    let mut out = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    let result = (|| {
+        let mut current = 0;
+        let mut typed = String::new();
+        loop {
+            render(&mut out, world, story, current, &typed)?;
+            if let Some(delay) = auto_advance {
+                if !event::poll(delay)? {
+                    current = (current + 1).min(story.len() - 1);
+                    continue;
+                }
+            }
+            match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => {
+                    match command(key, current, story.len(), &mut typed) {
+                        Command::Show(index) => current = index,
+                        Command::Quit => return Ok(()),
+                        Command::Nothing => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+    })();
+
+    execute!(out, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+#[test]
+fn command_test() {
+    let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+    let mut typed = String::new();
+    assert_eq!(
+        command(key(KeyCode::Right), 2, 5, &mut typed),
+        Command::Show(3)
+    );
+    assert_eq!(
+        command(key(KeyCode::Right), 4, 5, &mut typed),
+        Command::Show(4)
+    );
+    assert_eq!(
+        command(key(KeyCode::Left), 0, 5, &mut typed),
+        Command::Show(0)
+    );
+    assert_eq!(
+        command(key(KeyCode::End), 0, 5, &mut typed),
+        Command::Show(4)
+    );
+
+    assert_eq!(
+        command(key(KeyCode::Char('3')), 0, 5, &mut typed),
+        Command::Nothing
+    );
+    assert_eq!(
+        command(key(KeyCode::Enter), 0, 5, &mut typed),
+        Command::Show(2)
+    );
+    assert!(typed.is_empty());
+    command(key(KeyCode::Char('9')), 0, 5, &mut typed);
+    assert_eq!(
+        command(key(KeyCode::Enter), 0, 5, &mut typed),
+        Command::Show(4)
+    );
+
+    assert_eq!(
+        command(
+            KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            0,
+            5,
+            &mut typed
+        ),
+        Command::Quit
+    );
+}
@@ -0,0 +1,239 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{BorderStyle, Changes, Individual, Link, Node, StoryStep};
+
+fn map_ids(ids: &HashSet<usize>, map: &HashMap<usize, usize>) -> HashSet<usize> {
+    ids.iter().filter_map(|id| map.get(id)).copied().collect()
+}
+
+impl Individual {
+    // The deepest level that can be shown whole with at most max_nodes nodes,
+    // which is the deepest level of all when the chart is small enough. Nodes
+    // deeper than that are collapsed into their ancestor at that level.
+    fn collapse_depth(&self, max_nodes: usize) -> usize {
+        let deepest = self.nodes.iter().map(|n| n.depth).max().unwrap_or(0);
+        (0..=deepest)
+            .rev()
+            .find(|depth| self.nodes.iter().filter(|n| n.depth <= *depth).count() <= max_nodes)
+            .unwrap_or(0)
+    }
+
+    // Draw each subtree below the collapse depth as a single dashed box, named
+    // after its root. Links to the nodes inside go to the box instead, and the
+    // links inside it disappear. Subtrees with a node in `open` stay expanded.
+    fn collapse(
+        &self,
+        story: &[StoryStep],
+        max_nodes: usize,
+        open: &HashSet<usize>,
+    ) -> (Individual, Vec<StoryStep>) {
+        let descendants_ids = match &self.descendants_ids {
+            Some(ids) => ids.clone(),
+            None => {
+                let mut copy = self.clone();
+                copy.recompute_descendants_ids();
+                copy.descendants_ids.unwrap()
+            }
+        };
+        let depth = self.collapse_depth(max_nodes);
+
+        // Which node is drawn for each node, itself or the box it is inside
+        let mut drawn_as: Vec<usize> = (0..self.nodes.len()).collect();
+        let mut collapsed: HashSet<usize> = HashSet::new();
+        for node in self.nodes.iter().filter(|n| n.depth == depth) {
+            let inside = &descendants_ids[node.id][1..];
+            if inside.is_empty() || inside.iter().any(|id| open.contains(id)) {
+                continue;
+            }
+            collapsed.insert(node.id);
+            for id in inside {
+                drawn_as[*id] = node.id;
+            }
+        }
+
+        // Every node drawn gets its new id before any parent is looked up, so
+        // this doesn't depend on parents coming before their children
+        let drawn: Vec<&Node> = self
+            .nodes
+            .iter()
+            .filter(|n| drawn_as[n.id] == n.id)
+            .collect();
+        let mut node_map: HashMap<usize, usize> = drawn
+            .iter()
+            .enumerate()
+            .map(|(id, node)| (node.id, id))
+            .collect();
+        let mut nodes: Vec<Node> = vec![];
+        for (id, node) in drawn.into_iter().enumerate() {
+            nodes.push(Node {
+                id,
+                parent: node.parent.map(|parent| node_map[&parent]),
+                style: if collapsed.contains(&node.id) {
+                    BorderStyle::Dashed
+                } else {
+                    node.style
+                },
+                ..node.clone()
+            });
+        }
+        for (id, target) in drawn_as.iter().enumerate() {
+            node_map.insert(id, node_map[target]);
+        }
+
+        let mut link_map: HashMap<usize, usize> = HashMap::new();
+        let mut links: Vec<Link> = vec![];
+        for link in self.links.iter() {
+            let (from, to) = (node_map[&link.from], node_map[&link.to]);
+            if from == to && link.from != link.to {
+                continue;
+            }
+            // Links that now join the same boxes the same way with the same
+            // style are drawn once, with the label of the first one
+            let existing = links
+                .iter()
+                .find(|l| (l.from, l.to, l.style) == (from, to, link.style));
+            let id = match existing {
+                Some(existing) => existing.id,
+                None => {
+                    let id = links.len();
+                    links.push(Link {
+                        id,
+                        from,
+                        to,
+                        label: link.label.clone(),
+                        ..*link
+                    });
+                    id
+                }
+            };
+            link_map.insert(link.id, id);
+        }
+
+        let story = story
+            .iter()
+            .map(|step| StoryStep {
+                visible_nodes_ids: map_ids(&step.visible_nodes_ids, &node_map),
+                visible_link_ids: map_ids(&step.visible_link_ids, &link_map),
+                highlighted_nodes_ids: map_ids(&step.highlighted_nodes_ids, &node_map),
+                highlighted_link_ids: map_ids(&step.highlighted_link_ids, &link_map),
+                dim_others: step.dim_others,
+                changes: step.changes.as_ref().map(|changes| Changes {
+                    new_nodes_ids: map_ids(&changes.new_nodes_ids, &node_map),
+                    new_link_ids: map_ids(&changes.new_link_ids, &link_map),
+                }),
+                md: step.md.clone(),
+            })
+            .collect();
+
+        let mut individual = Individual {
+            nodes,
+            links,
+            width: self.width,
+            height: self.height,
+            descendants_ids: None,
+            fit_to_grid: self.fit_to_grid,
+        };
+        individual.set_fit_to_grid(self.fit_to_grid);
+        individual.recompute_descendants_ids();
+        (individual, story)
+    }
+
+    // Whether collapsing subtrees can bring the chart down to max_nodes nodes,
+    // which it can't when there are more top level nodes than that
+    pub fn can_simplify_to(&self, max_nodes: usize) -> bool {
+        self.nodes.iter().filter(|n| n.depth == 0).count() <= max_nodes
+    }
+
+    // When the chart has more than max_nodes nodes, collapse its deepest
+    // subtrees into single boxes until it doesn't, or only top level nodes are
+    // left
+    pub fn simplify(&self, story: &[StoryStep], max_nodes: usize) -> (Individual, Vec<StoryStep>) {
+        self.collapse(story, max_nodes, &HashSet::new())
+    }
+
+    // Like simplify, but the subtrees with a node highlighted by the step at
+    // `index` stay expanded, to draw that step with the details it talks about
+    pub fn simplify_for_step(
+        &self,
+        story: &[StoryStep],
+        index: usize,
+        max_nodes: usize,
+    ) -> (Individual, Vec<StoryStep>) {
+        self.collapse(story, max_nodes, &story[index].highlighted_nodes_ids)
+    }
+}
+
+#[test]
+fn simplify_test() {
+    let (world, story) = Individual::from_string(
+        [
+            "Cafe:Bar:Espresso Machine -> Cafe:Bar:Grinder",
+            "The machine needs beans.",
+            "Customer -> Cafe:Bar:Espresso Machine",
+            "The customer watches.",
+            "Customer -> Cafe:Kitchen:Oven",
+            "Then looks at the oven.",
+        ]
+        .join("\n")
+        .as_str(),
+        80,
+        40,
+    );
+    assert_eq!(world.nodes.len(), 7);
+    assert_eq!(world.simplify(&story, 7).0, world);
+    assert!(world.can_simplify_to(2));
+    assert!(!world.can_simplify_to(1));
+
+    // Bar and Kitchen become boxes, the links inside the bar disappear
+    let (simple, simple_story) = world.simplify(&story, 5);
+    let names: Vec<&str> = simple.nodes.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, ["Cafe", "Bar", "Customer", "Kitchen"]);
+    assert_eq!(simple.nodes[1].style, BorderStyle::Dashed);
+    assert_eq!(simple.nodes[1].parent, Some(0));
+    let links: Vec<(usize, usize)> = simple.links.iter().map(|l| (l.from, l.to)).collect();
+    assert_eq!(links, [(2, 1), (2, 3)]);
+    assert_eq!(simple_story[0].visible_link_ids, HashSet::from([]));
+    assert_eq!(
+        simple_story[1].highlighted_nodes_ids,
+        HashSet::from([0, 1, 2])
+    );
+    assert_eq!(simple_story[2].highlighted_link_ids, HashSet::from([1]));
+
+    // The kitchen is opened when the step talks about the oven
+    let (simple, simple_story) = world.simplify_for_step(&story, 2, 5);
+    let names: Vec<&str> = simple.nodes.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, ["Cafe", "Bar", "Customer", "Kitchen", "Oven"]);
+    assert_eq!(simple.nodes[3].style, BorderStyle::Solid);
+    assert!(simple_story[2].highlighted_nodes_ids.contains(&4));
+    assert!(simple.to_string(&simple_story[2]).contains("Oven"));
+
+    // Links going the same way merge, but not links in the other direction
+    // or with another style
+    use crate::LinkStyle;
+    let (world, story) = Individual::from_string(
+        [
+            "Customer -> Bar:Machine",
+            "Customer -> Bar:Grinder",
+            "Bar:Cup -> Customer",
+            "Customer --> Bar:Tray",
+        ]
+        .join("\n")
+        .as_str(),
+        80,
+        40,
+    );
+    let (simple, _) = world.simplify(&story, 2);
+    let links: Vec<(usize, usize, LinkStyle)> = simple
+        .links
+        .iter()
+        .map(|l| (l.from, l.to, l.style))
+        .collect();
+    assert_eq!(
+        links,
+        [
+            (0, 1, LinkStyle::Solid),
+            (1, 0, LinkStyle::Solid),
+            (0, 1, LinkStyle::Dashed)
+        ]
+    );
+}
@@ -0,0 +1,150 @@
+use crate::{Individual, StoryStep};
+
+// Steps without a chart, like the title before the first instruction, show
+// their text on the slide. Other steps show the chart, and their text becomes
+// the presenter notes.
+fn slide_parts(world: &Individual, step: &StoryStep) -> (String, String) {
+    if step.visible_nodes_ids.is_empty() {
+        (step.md.trim().to_string(), String::new())
+    } else {
+        (world.to_string(step), step.md.trim().to_string())
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const HTML_STYLE: &str = "
+body { margin: 0; background: #fff; color: #222; font-family: sans-serif; }
+section { display: none; height: 100vh; box-sizing: border-box; padding: 2em;
+  align-items: center; justify-content: center; flex-direction: column; }
+section.current { display: flex; }
+pre { font-family: monospace; line-height: 1.15; white-space: pre; }
+aside.notes { display: none; max-width: 60em; white-space: pre-wrap; color: #555; }
+body.notes aside.notes { display: block; }
+";
+
+// Arrows and space move between slides, n shows the notes
+const HTML_SCRIPT: &str = "
+const slides = document.querySelectorAll('section');
+let current = 0;
+function show(index) {
+  current = Math.max(0, Math.min(slides.length - 1, index));
+  slides.forEach((s, i) => s.classList.toggle('current', i === current));
+}
+document.addEventListener('keydown', (e) => {
+  if (['ArrowRight', 'ArrowDown', ' ', 'PageDown'].includes(e.key)) show(current + 1);
+  if (['ArrowLeft', 'ArrowUp', 'PageUp'].includes(e.key)) show(current - 1);
+  if (e.key === 'n') document.body.classList.toggle('notes');
+});
+show(0);
+";
+
+impl Individual {
+    // Markdown for reveal.js, one slide per step, with "Note:" starting the
+    // presenter notes
+    pub fn to_slides(&self, story: &[StoryStep]) -> String {
+        story
+            .iter()
+            .filter(|step| !step.visible_nodes_ids.is_empty() || !step.md.trim().is_empty())
+            .map(|step| {
+                let (body, notes) = slide_parts(self, step);
+                if notes.is_empty() {
+                    format!("{}\n", body.trim_end())
+                } else {
+                    format!("{}\n\nNote:\n{}\n", body.trim_end(), notes)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n---\n\n")
+    }
+
+    // A single html file that needs nothing else to run. The notes are in
+    // aside elements like reveal.js expects, and shown with the n key.
+    pub fn to_html_slides(&self, story: &[StoryStep]) -> String {
+        let title = story
+            .iter()
+            .flat_map(|step| step.md.lines())
+            .find_map(|line| line.strip_prefix("# "))
+            .unwrap_or("obm");
+
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n",
+            escape_html(title.trim()),
+            HTML_STYLE
+        );
+        for step in story {
+            if step.visible_nodes_ids.is_empty() && step.md.trim().is_empty() {
+                continue;
+            }
+            let (body, notes) = slide_parts(self, step);
+            out.push_str("<section>\n");
+            if step.visible_nodes_ids.is_empty() {
+                for line in body.lines().filter(|l| !l.trim().is_empty()) {
+                    let level = line.chars().take_while(|c| *c == '#').count();
+                    let text = escape_html(line[level..].trim());
+                    out.push_str(&match level {
+                        0 => format!("<p>{}</p>\n", text),
+                        _ => format!("<h{}>{}</h{}>\n", level.min(6), text, level.min(6)),
+                    });
+                }
+            } else {
+                // The chart is indented as a markdown code block
+                let chart: Vec<&str> = body
+                    .lines()
+                    .map(|line| line.strip_prefix("    ").unwrap_or(line))
+                    .collect();
+                out.push_str(&format!("<pre>{}</pre>\n", escape_html(&chart.join("\n"))));
+            }
+            if !notes.is_empty() {
+                out.push_str(&format!(
+                    "<aside class=\"notes\">{}</aside>\n",
+                    escape_html(&notes)
+                ));
+            }
+            out.push_str("</section>\n");
+        }
+        out.push_str(&format!(
+            "<script>{}</script>\n</body>\n</html>\n",
+            HTML_SCRIPT
+        ));
+        out
+    }
+}
+
+#[test]
+fn to_slides_test() {
+    let (mut individual, story) =
+        Individual::from_string("# Coffee\nCustomer -> Cafe\nThe customer <enters>.", 80, 40);
+    individual.nodes[0].position = crate::Rectangle {
+        x: 2,
+        y: 2,
+        w: 12,
+        h: 3,
+    };
+    individual.nodes[1].position = crate::Rectangle {
+        x: 20,
+        y: 2,
+        w: 8,
+        h: 3,
+    };
+    let chart = individual.to_string(&story[1]);
+
+    assert_eq!(
+        individual.to_slides(&story),
+        format!(
+            "# Coffee\n\n---\n\n{}\n\nNote:\nThe customer <enters>.\n",
+            chart.trim_end()
+        )
+    );
+
+    let html = individual.to_html_slides(&story);
+    assert!(html.contains("<title>Coffee</title>"));
+    assert!(html.contains("<section>\n<h1>Coffee</h1>\n</section>"));
+    assert!(html.contains("<aside class=\"notes\">The customer &lt;enters&gt;.</aside>"));
+    assert_eq!(html.matches("<section>").count(), 2);
+}